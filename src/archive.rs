@@ -0,0 +1,86 @@
+use crate::collision::BUNDLE_INDEX;
+use crate::date::{self, Zone};
+use crate::document::Document;
+use crate::error::Error;
+use crate::post;
+use crate::project::Project;
+use crate::relocate::move_post;
+use clap::Args;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Section old posts are moved to
+pub const ARCHIVE_SECTION: &str = "archive";
+
+#[derive(Args, Debug)]
+pub struct ArchiveArguments {
+    /// Archive the posts dated before this, e.g. `2020-01-01` or `2 years ago`
+    #[arg(long)]
+    before: String,
+
+    /// Set `archived = true` on the posts instead of moving them
+    #[arg(long)]
+    mark: bool,
+
+    /// Show which posts would be archived without changing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Runs the `archive` subcommand, moving the posts older than a date to the
+/// `archive` section, where they keep the sections they were in
+pub fn run(args: ArchiveArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = &project.config;
+    let zone = Zone::from_setting(config.timezone.as_deref())?;
+    let before = date::parse_date(&args.before, zone)?.date;
+    let content_dir = project.content_dir(config, project.preset)?;
+    let archive_dir = content_dir.join(ARCHIVE_SECTION);
+
+    let posts = post::load_posts(std::slice::from_ref(&content_dir))
+        .into_iter()
+        .filter(|p| !p.path.starts_with(&archive_dir))
+        .filter(|p| {
+            p.date(zone, config.date_format.as_deref())
+                .is_some_and(|d| d.date_naive() < before)
+        })
+        .filter(|p| post::field(&p.front_matter, "archived").and_then(Value::as_bool) != Some(true))
+        .collect::<Vec<_>>();
+
+    for post in posts {
+        let relative = post.path.strip_prefix(current_dir).unwrap_or(&post.path);
+        if args.dry_run {
+            println!("{}  {}", relative.display(), post.title());
+        } else if args.mark {
+            let mut document = Document::read(&post.path)?;
+            document.set_custom("archived", Value::Boolean(true), project.preset);
+            document.write()?;
+            println!("{}", relative.display());
+        } else {
+            let section = archive_section(&content_dir, &post.path);
+            let new_path = move_post(&project, &post.path, &section.to_string_lossy())?;
+            println!(
+                "{} -> {}",
+                relative.display(),
+                new_path
+                    .strip_prefix(current_dir)
+                    .unwrap_or(&new_path)
+                    .display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// The archive's section the post goes in, named after the one it's in now
+fn archive_section(content_dir: &Path, path: &Path) -> PathBuf {
+    let dir = match path.file_stem() {
+        Some(stem) if stem == BUNDLE_INDEX => path.parent().and_then(Path::parent),
+        _ => path.parent(),
+    };
+    let section = dir
+        .and_then(|d| d.strip_prefix(content_dir).ok())
+        .unwrap_or(Path::new(""));
+    Path::new(ARCHIVE_SECTION).join(section)
+}
@@ -0,0 +1,178 @@
+use std::fs;
+use std::path::Path;
+use toml::value::Datetime;
+use toml::{Table, Value};
+
+const CONTENT_CONFIG_FILES: [&str; 6] = [
+    "src/content.config.ts",
+    "src/content.config.mjs",
+    "src/content.config.js",
+    "src/content/config.ts",
+    "src/content/config.mjs",
+    "src/content/config.js",
+];
+
+/// A field declared in a content collection's zod schema
+#[derive(Debug)]
+pub struct Field {
+    pub name: String,
+    pub kind: FieldKind,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    String,
+    Date,
+    Number,
+    Boolean,
+    Array,
+    Other,
+}
+
+impl FieldKind {
+    fn from_expression(expression: &str) -> Self {
+        let expression = expression.trim_start();
+        if expression.starts_with("z.string") || expression.starts_with("image(") {
+            FieldKind::String
+        } else if expression.starts_with("z.date") || expression.starts_with("z.coerce.date") {
+            FieldKind::Date
+        } else if expression.starts_with("z.number") || expression.starts_with("z.coerce.number") {
+            FieldKind::Number
+        } else if expression.starts_with("z.boolean") {
+            FieldKind::Boolean
+        } else if expression.starts_with("z.array") {
+            FieldKind::Array
+        } else {
+            FieldKind::Other
+        }
+    }
+
+    /// Value written for a required field nothing else filled in
+    pub fn placeholder(&self, date: Datetime) -> Value {
+        match self {
+            FieldKind::String | FieldKind::Other => Value::String(String::new()),
+            FieldKind::Date => Value::Datetime(date),
+            FieldKind::Number => Value::Integer(0),
+            FieldKind::Boolean => Value::Boolean(false),
+            FieldKind::Array => Value::Array(Vec::new()),
+        }
+    }
+}
+
+/// Reads the schema declared for `collection` in the site's content config.
+/// Returns no fields if there is no config, or the collection isn't in it.
+pub fn collection_fields(root: &Path, collection: &str) -> Vec<Field> {
+    CONTENT_CONFIG_FILES
+        .iter()
+        .find_map(|f| fs::read_to_string(root.join(f)).ok())
+        .and_then(|config| find_schema(&config, collection).map(parse_schema))
+        .unwrap_or_default()
+}
+
+/// Adds placeholders for the required fields missing from `table`
+pub fn fill_required_fields(table: &mut Table, fields: &[Field], date: Datetime) {
+    for field in fields.iter().filter(|f| f.required) {
+        table
+            .entry(field.name.clone())
+            .or_insert_with(|| field.kind.placeholder(date));
+    }
+}
+
+// Finds the body of the `z.object({ ... })` passed as schema to the collection,
+// either declared as `const blog = defineCollection(...)` or inline as
+// `blog: defineCollection(...)`
+fn find_schema<'a>(config: &'a str, collection: &str) -> Option<&'a str> {
+    let definitions = config
+        .match_indices("defineCollection(")
+        .collect::<Vec<_>>();
+
+    let (start, _) = definitions
+        .iter()
+        .find(|(i, _)| {
+            let before = config[..*i].trim_end();
+            let name = before
+                .strip_suffix(['=', ':'])
+                .unwrap_or(before)
+                .trim_end()
+                .trim_end_matches(['"', '\'']);
+            name.rsplit(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .next()
+                == Some(collection)
+        })
+        .or_else(|| definitions.first().filter(|_| definitions.len() == 1))?;
+
+    let object = &config[*start..];
+    let object = &object[object.find("z.object(")? + "z.object(".len()..];
+    let object = &object[object.find('{')?..];
+
+    Some(&object[1..matching_brace(object)?])
+}
+
+// Position of the brace closing the one `text` starts with
+fn matching_brace(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn parse_schema(schema: &str) -> Vec<Field> {
+    split_top_level(schema)
+        .into_iter()
+        .filter_map(|entry| {
+            let entry = entry
+                .lines()
+                .filter(|l| !l.trim_start().starts_with("//"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let (name, expression) = entry.split_once(':')?;
+            let name = name.trim().trim_matches(['"', '\'']);
+            if name.is_empty() {
+                return None;
+            }
+
+            let required = !["optional()", "default(", "nullish()"]
+                .iter()
+                .any(|modifier| expression.contains(modifier));
+
+            Some(Field {
+                name: name.to_string(),
+                kind: FieldKind::from_expression(expression),
+                required,
+            })
+        })
+        .collect()
+}
+
+// Splits the object body on the commas separating its properties
+fn split_top_level(schema: &str) -> Vec<&str> {
+    let mut entries = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in schema.char_indices() {
+        match c {
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                entries.push(schema[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    entries.push(schema[start..].trim());
+
+    entries.into_iter().filter(|e| !e.is_empty()).collect()
+}
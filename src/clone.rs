@@ -0,0 +1,107 @@
+use crate::collision::BUNDLE_INDEX;
+use crate::document::Document;
+use crate::error::Error;
+use crate::template::Template;
+use crate::{create_post, PostOptions};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+/// Fields of the existing post the copy doesn't get, besides the ones that are
+/// always its own like the title and date
+const RESET_FIELDS: [&str; 5] = [
+    "draft",
+    "aliases",
+    "publishDate",
+    "expiryDate",
+    "reading_time",
+];
+
+#[derive(Args, Debug)]
+pub struct CloneArguments {
+    /// Post to copy
+    post: PathBuf,
+
+    /// Title of the new post
+    title: String,
+
+    #[command(flatten)]
+    options: PostOptions,
+}
+
+/// Runs the `clone` subcommand, creating a post with the front matter of an
+/// existing one and the headings of its body
+pub fn run(args: CloneArguments, current_dir: &Path) -> Result<(), Error> {
+    let path = current_dir.join(&args.post);
+    // Read like any other post, so every front matter format works
+    let document = Document::read(&path)?;
+    let is_org = path.extension().is_some_and(|e| e == "org");
+    let mut template = Template {
+        front_matter: document.table(),
+        body: skeleton(document.body(), is_org),
+    };
+    for key in RESET_FIELDS {
+        template.front_matter.remove(key);
+        if let Some(extra) = template
+            .front_matter
+            .get_mut("extra")
+            .and_then(|e| e.as_table_mut())
+        {
+            extra.remove(key);
+        }
+    }
+
+    // Next to the existing post, unless told otherwise
+    let mut options = args.options;
+    if options.section.is_none() && options.dir.is_none() && options.site.is_none() {
+        let is_bundle = path.file_stem().is_some_and(|s| s == BUNDLE_INDEX);
+        let dir = if is_bundle {
+            path.parent().and_then(Path::parent)
+        } else {
+            path.parent()
+        };
+        options.dir = dir.map(Path::to_path_buf);
+        options.bundle |= is_bundle;
+    }
+
+    create_post(
+        &args.title,
+        Vec::new(),
+        "",
+        None,
+        Some(template),
+        options,
+        current_dir,
+    )
+}
+
+// The headings of a body, with the text under them and code blocks left out.
+// Org's headings start with `*`, and Markdown's with `#`.
+fn skeleton(body: &str, is_org: bool) -> String {
+    let mut headings = String::new();
+    let mut fence = None;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if let Some(end) = fence {
+            if trimmed.to_lowercase().starts_with(end) {
+                fence = None;
+            }
+            continue;
+        }
+
+        fence = if is_org {
+            trimmed
+                .to_lowercase()
+                .starts_with("#+begin_")
+                .then_some("#+end_")
+        } else {
+            ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f))
+        };
+        let marker = if is_org { '*' } else { '#' };
+        let is_heading =
+            line.starts_with(marker) && line.trim_start_matches(marker).starts_with(' ');
+        if fence.is_none() && is_heading {
+            headings.push_str(&format!("\n{}\n", line));
+        }
+    }
+    headings
+}
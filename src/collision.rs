@@ -0,0 +1,90 @@
+use crate::error::Error;
+use crate::prompt;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// What to do when the file for a new post already exists
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnCollision {
+    /// Stop without touching the existing file
+    #[default]
+    Abort,
+    /// Add a number to the new file's name, e.g. `my-post-2.md`
+    Suffix,
+    /// Ask what to do
+    Prompt,
+    /// Replace the existing file
+    Overwrite,
+    /// Open the existing file in the editor instead of creating a new one
+    Open,
+}
+
+/// Name of the post's file in a page bundle, without the extension
+pub const BUNDLE_INDEX: &str = "index";
+
+/// Where the post ends up
+pub enum Target {
+    /// A file to write the new post to
+    New(PathBuf),
+    /// An existing post to open as is
+    Existing(PathBuf),
+}
+
+/// Picks the path to write the new post to when `path` is already taken
+pub fn resolve(path: PathBuf, on_collision: OnCollision) -> Result<Target, Error> {
+    if !path.exists() {
+        return Ok(Target::New(path));
+    }
+
+    match on_collision {
+        OnCollision::Abort => Err(Error::from_string(
+            format!(
+                "'{}' already exists, pass --force to overwrite it",
+                path.display()
+            )
+            .as_str(),
+        )),
+        OnCollision::Suffix => Ok(Target::New(with_free_suffix(&path))),
+        OnCollision::Overwrite => Ok(Target::New(path)),
+        OnCollision::Open => Ok(Target::Existing(path)),
+        OnCollision::Prompt if !prompt::is_interactive() => resolve(path, OnCollision::Abort),
+        OnCollision::Prompt => {
+            let question = format!(
+                "'{}' already exists. [o]verwrite, add a [s]uffix, [e]dit it or [a]bort?",
+                path.display()
+            );
+            match prompt::ask(&question)?.to_lowercase().as_str() {
+                "o" | "overwrite" => Ok(Target::New(path)),
+                "s" | "suffix" => Ok(Target::New(with_free_suffix(&path))),
+                "e" | "edit" => Ok(Target::Existing(path)),
+                _ => Err(Error::from_string("Aborted")),
+            }
+        }
+    }
+}
+
+/// The path with the lowest free number added to the name, of the bundle
+/// directory for posts in page bundles
+pub fn with_free_suffix(path: &Path) -> PathBuf {
+    if let (Some(bundle), Some(file_name)) = (path.parent(), path.file_name()) {
+        if path.file_stem().is_some_and(|s| s == BUNDLE_INDEX) {
+            return with_free_suffix(bundle).join(file_name);
+        }
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    (2..)
+        .map(|n| path.with_file_name(format!("{}-{}{}", stem, n, extension)))
+        .find(|p| !p.exists())
+        .expect("there is always a free number")
+}
@@ -0,0 +1,130 @@
+use crate::error::Error;
+use crate::Arguments;
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+use std::env;
+use std::path::Path;
+
+#[derive(Args, Debug)]
+pub struct CompletionsArguments {
+    /// Shell to complete in, tags are completed from the site in bash, zsh
+    /// and fish
+    shell: Shell,
+}
+
+/// Runs the `completions` subcommand, printing the completion script for the
+/// shell, named after the program as it was run
+pub fn run(args: CompletionsArguments, _current_dir: &Path) -> Result<(), Error> {
+    let name = env::args_os()
+        .next()
+        .and_then(|a| Some(Path::new(&a).file_stem()?.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "new-post".to_string());
+
+    let mut command = Arguments::command();
+    let mut script = Vec::new();
+    clap_complete::generate(args.shell, &mut command, &name, &mut script);
+    let script = String::from_utf8_lossy(&script);
+
+    let script = match args.shell {
+        Shell::Bash => complete_tags_in_bash(&script, &name, &command),
+        Shell::Zsh => complete_tags_in_zsh(&script, &name),
+        Shell::Fish => complete_tags_in_fish(&script, &name),
+        _ => script.to_string(),
+    };
+    print!("{}", script);
+    Ok(())
+}
+
+// The generated function is wrapped by one that completes the words after the
+// title with the site's tags, unless they're a subcommand's or an option's
+fn complete_tags_in_bash(script: &str, name: &str, command: &clap::Command) -> String {
+    let Some(function) = script.split_whitespace().skip_while(|w| *w != "-F").nth(1) else {
+        return script.to_string();
+    };
+
+    let subcommands = command
+        .get_subcommands()
+        .map(|c| c.get_name())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let value_options = command
+        .get_arguments()
+        .filter(|a| !a.is_positional() && a.get_action().takes_values())
+        .flat_map(|a| {
+            a.get_long()
+                .map(|l| format!("--{}", l))
+                .into_iter()
+                .chain(a.get_short().map(|s| format!("-{}", s)))
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let wrapper = format!("{}_tags", function);
+
+    format!(
+        r#"{script}
+{wrapper}() {{
+    {function} "$@"
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    if [[ ${{COMP_CWORD}} -ge 2 && "${{cur}}" != -* ]] \
+        && [[ " {subcommands} " != *" ${{COMP_WORDS[1]}} "* ]] \
+        && [[ " --tag -T " == *" ${{prev}} "* || " {value_options} " != *" ${{prev}} "* ]]; then
+        COMPREPLY=($(compgen -W "$({name} tags --names-only 2>/dev/null)" -- "${{cur}}"))
+    fi
+}}
+complete -F {wrapper} -o bashdefault -o default {name}
+"#
+    )
+}
+
+// The `_default` completion of the tags argument and `--tag` is swapped for
+// the site's tags
+fn complete_tags_in_zsh(script: &str, name: &str) -> String {
+    let function = format!("_{}_site_tags", name.replace('-', "_"));
+    let lines = script.lines().map(|line| {
+        let is_tags = line.starts_with("'::tags -- ")
+            || line.starts_with("'*::tags -- ")
+            || line.ends_with("]:TAG:_default' \\");
+        match line.strip_suffix(":_default' \\") {
+            Some(spec) if is_tags => format!("{}:{}' \\", spec, function),
+            Some(_) | None => line.to_string(),
+        }
+    });
+
+    let mut completed = String::new();
+    for (i, line) in lines.enumerate() {
+        completed.push_str(&line);
+        completed.push('\n');
+        // After `#compdef`, so it's there when the completion is autoloaded
+        if i == 0 {
+            completed.push_str(&format!(
+                r#"
+{function}() {{
+    local -a tags
+    tags=(${{(f)"$({name} tags --names-only 2>/dev/null)"}})
+    compadd -a tags
+}}
+"#
+            ));
+        }
+    }
+    completed
+}
+
+fn complete_tags_in_fish(script: &str, name: &str) -> String {
+    let condition = format!("__fish_{}_needs_command", name.replace('-', "_"));
+    let tags = format!("-f -a \"({} tags --names-only 2>/dev/null)\"", name);
+    let mut completed = String::new();
+    for line in script.lines() {
+        completed.push_str(line);
+        if line.contains(" -l tag ") {
+            completed.push(' ');
+            completed.push_str(&tags);
+        }
+        completed.push('\n');
+    }
+    completed.push_str(&format!(
+        "complete -c {} -n \"{}\" {}\n",
+        name, condition, tags
+    ));
+    completed
+}
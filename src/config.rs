@@ -0,0 +1,463 @@
+use crate::collision::OnCollision;
+use crate::editor::{OpenWith, Pane};
+use crate::error::Error;
+use crate::front_matter::{Extension, Format, IdKind};
+use crate::kind::Kind;
+use crate::language::LanguagesConfig;
+use crate::preset::Preset;
+use crate::section::IndexConfig;
+use crate::slug::SlugConfig;
+use crate::tags::TagRules;
+use clap::Subcommand;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Table;
+use toml_edit::DocumentMut;
+
+pub const PROJECT_CONFIG_FILES: [&str; 2] = [".newpost.toml", "newpost.toml"];
+
+/// Settings can be overridden with environment variables named after them,
+/// e.g. `NEW_POST_CONTENT_DIR` for `content_dir`
+pub const ENVIRONMENT_PREFIX: &str = "NEW_POST_";
+
+/// Selects a profile like `--profile`, rather than overriding a setting
+pub const PROFILE_VARIABLE: &str = "NEW_POST_PROFILE";
+
+/// Defaults for the command line options, read from the config files
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub format: Option<Format>,
+    pub ext: Option<Extension>,
+    pub preset: Option<Preset>,
+    /// Content directory, relative to the project root unless absolute
+    pub content_dir: Option<PathBuf>,
+    /// Names of the content directory to look for, in order, e.g.
+    /// `["content", "_posts", "src/posts"]`
+    pub content_dirs: Option<Vec<String>>,
+    /// Directories of the sites in a monorepo, by name, for `--site`
+    pub sites: BTreeMap<String, PathBuf>,
+    /// Files that mark the root of the site, see [`crate::project::ROOT_MARKERS`]
+    pub root_markers: Option<Vec<String>>,
+    /// Authors of new posts, instead of the name in the git config. Set to an
+    /// empty list to leave the field out.
+    pub authors: Option<Vec<String>>,
+    /// Tags added to every new post
+    pub tags: Vec<String>,
+    /// Categories added to every new post
+    pub categories: Vec<String>,
+    /// Taxonomies new posts have, e.g. `["tags", "categories", "series"]`
+    pub taxonomies: Option<Vec<String>>,
+    pub editor: Option<String>,
+    /// Whether posts are opened in the editor or the system's default application
+    pub open: Option<OpenWith>,
+    /// Return as soon as the editor has started instead of waiting for it to close
+    pub detach: bool,
+    /// Terminal pane to open the editor in, e.g. `tmux` or `auto`
+    pub pane: Option<Pane>,
+    /// Keep new posts the editor was closed on without changes
+    pub keep_empty: bool,
+    /// Pattern for the title of posts in a series, e.g. `{title} (part {part})`
+    pub series_title: Option<String>,
+    /// Pattern for the name of new files, see [`crate::expand_file_name`]
+    pub filename: Option<String>,
+    /// What to do when the new file already exists
+    pub on_collision: Option<OnCollision>,
+    /// Language of new posts, e.g. `de`, see [`crate::slug::create_safe_file_name`]
+    pub lang: Option<String>,
+    pub languages: LanguagesConfig,
+    /// Create posts as drafts
+    pub draft: bool,
+    /// Ask which tags a post gets when none are given, and there's a user to ask
+    pub pick_tags: Option<bool>,
+    pub tag_rules: TagRules,
+    /// Write the reading time to posts when they're published
+    pub reading_time: bool,
+    /// URL of the site, for the URLs of published posts, instead of the one in
+    /// the site generator's config
+    pub base_url: Option<String>,
+    /// Timezone of dates, e.g. `Europe/Oslo`, instead of the machine's
+    pub timezone: Option<String>,
+    /// strftime style format for dates, e.g. `%Y-%m-%d %H:%M:%S%z`
+    pub date_format: Option<String>,
+    /// Include the time of day in dates, like `--now`
+    pub timestamps: Option<Timestamps>,
+    /// Give new posts an updated date
+    pub updated: bool,
+    /// Create posts as page bundles, `<slug>/index.md`
+    pub bundle: bool,
+    /// Give section directories created for new posts an `_index.md`
+    pub section_index: bool,
+    pub index: IndexConfig,
+    /// Directory drafts are created in instead of the content directory
+    pub drafts_dir: Option<PathBuf>,
+    pub slug: SlugConfig,
+    /// Front matter fields to write, in order, instead of the generated ones
+    pub fields: Option<Vec<String>>,
+    /// Template new posts start from, see [`crate::template::Template`]
+    pub template: Option<String>,
+    /// Kind of post new posts start as, when there's no template
+    pub kind: Option<Kind>,
+    /// Values templates can use, like `{{ newsletter }}`
+    pub variables: Table,
+    /// Blocks the `snippet` subcommand adds to posts, by name
+    pub snippets: BTreeMap<String, String>,
+    pub layout: Option<String>,
+    pub permalink: Option<String>,
+    /// Give new posts an `id`, like `--id`
+    pub id: Option<IdKind>,
+    /// Section, or Astro collection, new posts are added to
+    #[serde(alias = "collection")]
+    pub section: Option<String>,
+}
+
+/// Either `timestamps = true` or `timestamps = { toml = true, yaml = false }`
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum Timestamps {
+    All(bool),
+    PerFormat {
+        toml: Option<bool>,
+        yaml: Option<bool>,
+        json: Option<bool>,
+    },
+}
+
+impl Timestamps {
+    /// Whether front matter in `format` has times, if that's been set
+    pub fn for_format(&self, format: Format) -> Option<bool> {
+        match (self, format) {
+            (Timestamps::All(timestamps), _) => Some(*timestamps),
+            (Timestamps::PerFormat { toml, .. }, Format::Toml) => *toml,
+            (Timestamps::PerFormat { yaml, .. }, Format::Yaml) => *yaml,
+            (Timestamps::PerFormat { json, .. }, Format::Json) => *json,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Print the value of a setting, e.g. `format` or `profiles.notes.tags`
+    Get { key: String },
+    /// Change a setting, the value is read as TOML if possible and as a string otherwise
+    Set { key: String, value: String },
+    /// Print all settings
+    List,
+}
+
+impl Config {
+    /// Reads the config layers, with later layers overriding earlier ones:
+    /// the user's global config, the project config, the selected profile,
+    /// the section's settings, then environment variables
+    pub fn load(
+        project_config: Option<&Path>,
+        profile: Option<&str>,
+        section: Option<&str>,
+    ) -> Result<Config, Error> {
+        let mut table = load_table(project_config)?;
+
+        let mut profiles = take_tables(&mut table, "profiles")?;
+        if let Some(name) = profile {
+            let profile = profiles.remove(name).ok_or(Error::from_string(
+                format!(
+                    "Unknown profile '{}', the configured profiles are: {}",
+                    name,
+                    profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+                .as_str(),
+            ))?;
+            merge(&mut table, profile);
+        }
+
+        // Sections don't need to be configured to be used
+        let mut sections = take_tables(&mut table, "sections")?;
+        if let Some(section) = section.and_then(|s| sections.remove(s.trim_matches('/'))) {
+            merge(&mut table, section);
+        }
+
+        merge(&mut table, environment_table()?);
+
+        Config::from_table(table)
+    }
+
+    fn from_table(table: Table) -> Result<Config, Error> {
+        Config::deserialize(table).map_err(|e| Error::from_error("Invalid configuration", &e))
+    }
+
+    /// Checks that a config file's contents, including its profiles and
+    /// sections, are valid
+    fn validate(mut table: Table) -> Result<(), Error> {
+        for (_, profile) in take_tables(&mut table, "profiles")? {
+            Config::validate(profile)?;
+        }
+        for (_, section) in take_tables(&mut table, "sections")? {
+            Config::from_table(section)?;
+        }
+
+        Config::from_table(table).map(|_| ())
+    }
+}
+
+/// Runs the `config` subcommand. Without `global` settings are read from all
+/// config files and written to the project config.
+pub fn run(action: ConfigAction, global: bool, current_dir: &Path) -> Result<(), Error> {
+    let project_config = find_project_config(current_dir);
+    let table = if global {
+        match global_config_path().filter(|p| p.is_file()) {
+            Some(path) => read_table(&path)?,
+            None => Table::new(),
+        }
+    } else {
+        load_table(project_config.as_deref())?
+    };
+
+    match action {
+        ConfigAction::Get { key } => {
+            let table = toml::Value::Table(table);
+            let value = key
+                .split('.')
+                .try_fold(&table, |value, k| value.get(k))
+                .ok_or(Error::from_string(format!("'{}' is not set", key).as_str()))?;
+            match value {
+                toml::Value::String(s) => println!("{}", s),
+                toml::Value::Table(t) => print!("{}", t),
+                _ => println!("{}", value),
+            }
+        }
+        ConfigAction::List => print!("{}", table),
+        ConfigAction::Set { key, value } => {
+            let path = if global {
+                global_config_path().ok_or(Error::from_string(
+                    "Unable to find the directory for the global config",
+                ))?
+            } else {
+                project_config.unwrap_or_else(|| current_dir.join(PROJECT_CONFIG_FILES[0]))
+            };
+            set_value(&path, &key, &value)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Edits the file in place so comments and formatting are kept
+fn set_value(path: &Path, key: &str, value: &str) -> Result<(), Error> {
+    let contents = if path.is_file() {
+        fs::read_to_string(path)
+            .map_err(|e| Error::from_error(&format!("Failed to read '{}'", path.display()), &e))?
+    } else {
+        String::new()
+    };
+    let mut document = contents
+        .parse::<DocumentMut>()
+        .map_err(|e| Error::from_error(&format!("Failed to parse '{}'", path.display()), &e))?;
+
+    let value = value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| toml_edit::Value::from(value));
+
+    let mut keys = key.split('.').collect::<Vec<_>>();
+    let last = keys
+        .pop()
+        .filter(|k| !k.is_empty())
+        .ok_or(Error::from_string("Empty key"))?;
+    let mut item = document.as_item_mut();
+    for k in keys {
+        item = &mut item[k];
+        if item.is_none() {
+            let mut table = toml_edit::Table::new();
+            table.set_implicit(true);
+            *item = toml_edit::Item::Table(table);
+        }
+    }
+    item[last] = toml_edit::value(value);
+
+    let contents = document.to_string();
+    Config::validate(
+        contents
+            .parse::<Table>()
+            .map_err(|e| Error::from_error("Invalid configuration", &e))?,
+    )?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| Error::from_error(&format!("Failed to create '{}'", dir.display()), &e))?;
+    }
+    fs::write(path, contents)
+        .map_err(|e| Error::from_error(&format!("Failed to write '{}'", path.display()), &e))
+}
+
+// The global and project config merged
+fn load_table(project_config: Option<&Path>) -> Result<Table, Error> {
+    let mut table = Table::new();
+
+    if let Some(path) = global_config_path().filter(|p| p.is_file()) {
+        merge(&mut table, read_table(&path)?);
+    }
+
+    if let Some(path) = project_config {
+        merge(&mut table, read_table(path)?);
+    }
+
+    Ok(table)
+}
+
+// The settings given as environment variables. Variables that aren't named
+// after a setting are left out, and values that don't fit the setting as TOML
+// are taken as strings, so `NEW_POST_EDITOR=true` is the `true` command.
+fn environment_table() -> Result<Table, Error> {
+    let mut table = Table::new();
+    for (name, value) in env::vars().filter(|(name, _)| name != PROFILE_VARIABLE) {
+        let Some(key) = name
+            .strip_prefix(ENVIRONMENT_PREFIX)
+            .map(str::to_lowercase)
+            .filter(|k| setting_names().contains(&k.as_str()))
+        else {
+            continue;
+        };
+        let check = |value: &toml::Value| {
+            Config::deserialize(Table::from_iter([(key.clone(), value.clone())]))
+        };
+
+        let parsed = parse_setting(&value);
+        let text = toml::Value::String(value);
+        let value = match (check(&parsed), check(&text)) {
+            (Ok(_), _) => parsed,
+            (Err(_), Ok(_)) => text,
+            (Err(e), Err(_)) => {
+                return Err(Error::from_error(&format!("Invalid {}", name), &e));
+            }
+        };
+        table.insert(key, value);
+    }
+    Ok(table)
+}
+
+// The names of the settings, the fields of `Config` as its `Deserialize`
+// implementation has them, with their aliases
+fn setting_names() -> &'static [&'static str] {
+    struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> serde::Deserializer<'de> for FieldNames<'_> {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(
+            self,
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom(
+                "only the struct's fields are read",
+            ))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(serde::de::Error::custom(
+                "only the struct's fields are read",
+            ))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = Config::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Reads a setting's value given as TOML, e.g. `true` or `["a", "b"]`, and
+/// falls back to taking it as a string
+pub fn parse_setting(value: &str) -> toml::Value {
+    format!("value = {}", value)
+        .parse::<Table>()
+        .ok()
+        .filter(|t| t.len() == 1)
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+// Removes a table of named tables, like `profiles`, from the settings
+fn take_tables(table: &mut Table, key: &str) -> Result<BTreeMap<String, Table>, Error> {
+    let invalid = || {
+        Error::from_string(
+            format!("Invalid configuration: `{}` must be a table of tables", key).as_str(),
+        )
+    };
+
+    match table.remove(key) {
+        Some(toml::Value::Table(tables)) => tables
+            .into_iter()
+            .map(|(name, value)| match value {
+                toml::Value::Table(t) => Ok((name, t)),
+                _ => Err(invalid()),
+            })
+            .collect(),
+        Some(_) => Err(invalid()),
+        None => Ok(Default::default()),
+    }
+}
+
+/// Location of the user's config file, in `$XDG_CONFIG_HOME/new-post/` or the
+/// platform's equivalent
+pub fn global_config_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            if cfg!(windows) {
+                env::var_os("APPDATA").map(PathBuf::from)
+            } else {
+                env::var_os("HOME").map(|h| PathBuf::from(h).join(".config"))
+            }
+        })?;
+
+    Some(config_dir.join("new-post").join("config.toml"))
+}
+
+/// Expands a leading `~` to the user's home directory
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Finds the project config file in `dir` or the closest of its ancestors
+pub fn find_project_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|d| PROJECT_CONFIG_FILES.iter().map(move |f| d.join(f)))
+        .find(|f| f.is_file())
+}
+
+pub fn read_table(path: &Path) -> Result<Table, Error> {
+    fs::read_to_string(path)
+        .map_err(|e| Error::from_error(&format!("Failed to read '{}'", path.display()), &e))?
+        .parse::<Table>()
+        .map_err(|e| Error::from_error(&format!("Failed to parse '{}'", path.display()), &e))
+}
+
+// Nested tables are merged key by key, any other value is replaced
+fn merge(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge(base, overrides)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use crate::error::Error;
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+/// Timezone dates are written in
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Zone {
+    /// The machine's timezone
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    /// Reads an IANA timezone name like `Europe/Oslo`, or `local`
+    pub fn parse(name: &str) -> Result<Zone, Error> {
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(Zone::Local);
+        }
+
+        name.parse()
+            .map(Zone::Named)
+            .map_err(|_| Error::from_string(format!("Unknown timezone '{}'", name).as_str()))
+    }
+
+    /// The configured timezone, or the machine's if there is none
+    pub fn from_setting(name: Option<&str>) -> Result<Zone, Error> {
+        name.map_or(Ok(Zone::Local), Zone::parse)
+    }
+
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.convert(Utc::now().fixed_offset())
+    }
+
+    pub fn convert(&self, datetime: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            Zone::Local => datetime.with_timezone(&Local).fixed_offset(),
+            Zone::Named(tz) => datetime.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// The date and time in the timezone, the earlier one if the clocks were
+    /// turned back at that time, and the first time after the gap if they were
+    /// turned forward
+    pub fn at(&self, date: NaiveDate, time: NaiveTime) -> DateTime<FixedOffset> {
+        match self {
+            Zone::Local => resolve(&Local, date.and_time(time)),
+            Zone::Named(tz) => resolve(tz, date.and_time(time)),
+        }
+    }
+}
+
+// Gaps are usually an hour, but a timezone may skip a whole day, like
+// Pacific/Apia did at the end of 2011
+fn resolve<Z: TimeZone>(zone: &Z, datetime: NaiveDateTime) -> DateTime<FixedOffset> {
+    (0..=2 * 24 * 4)
+        .find_map(|quarter| {
+            let later = datetime + chrono::Duration::minutes(15 * quarter);
+            zone.from_local_datetime(&later).earliest()
+        })
+        .unwrap_or_else(|| zone.from_utc_datetime(&datetime))
+        .fixed_offset()
+}
+
+/// A date given on the command line, the time is only there if it was given
+#[derive(Clone, Copy, Debug)]
+pub struct PostDate {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+}
+
+/// Reads a date like `2024-06-01`, `2024-06-01 14:00`, `yesterday`,
+/// `next monday`, `in 3 days` or `tomorrow 9:30`
+pub fn parse_date(input: &str, zone: Zone) -> Result<PostDate, Error> {
+    parse_relative_to(input, zone, zone.now().naive_local()).ok_or(Error::from_string(
+        format!("'{}' isn't a date this understands", input).as_str(),
+    ))
+}
+
+// Reads the date relative to `now`, the time in `zone`
+fn parse_relative_to(input: &str, zone: Zone, now: NaiveDateTime) -> Option<PostDate> {
+    let input = input.trim().to_lowercase();
+    let today = now.date();
+
+    if input == "now" {
+        return Some(PostDate {
+            date: today,
+            time: Some(now.time()),
+        });
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(&input.to_uppercase()) {
+        let datetime = zone.convert(datetime).naive_local();
+        return Some(PostDate {
+            date: datetime.date(),
+            time: Some(datetime.time()),
+        });
+    }
+    for format in ["%Y-%m-%dt%H:%M:%S", "%Y-%m-%dt%H:%M"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(&input, format) {
+            return Some(PostDate {
+                date: datetime.date(),
+                time: Some(datetime.time()),
+            });
+        }
+    }
+
+    let mut words = input
+        .split_whitespace()
+        .filter(|w| *w != "at")
+        .collect::<Vec<_>>();
+    let time = match words.last().and_then(|w| parse_time(w)) {
+        Some(time) => {
+            words.pop();
+            Some(time)
+        }
+        None => None,
+    };
+
+    let date = match words.as_slice() {
+        [] if time.is_some() => today,
+        ["today"] => today,
+        ["yesterday"] => today.pred_opt()?,
+        ["tomorrow"] => today.succ_opt()?,
+        [date] => match parse_weekday(date) {
+            Some(weekday) => next_weekday(today, weekday),
+            None => NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?,
+        },
+        ["next", "week"] => today + Days::new(7),
+        ["last", "week"] => today - Days::new(7),
+        ["next", weekday] => next_weekday(today, parse_weekday(weekday)?),
+        ["last", weekday] => {
+            let weekday = parse_weekday(weekday)?;
+            let days_back =
+                (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday() - 1)
+                    % 7
+                    + 1;
+            today - Days::new(days_back as u64)
+        }
+        ["in", count, unit] => shift(today, count.parse().ok()?, unit, true)?,
+        [count, unit, "ago"] => shift(today, count.parse().ok()?, unit, false)?,
+        _ => return None,
+    };
+
+    Some(PostDate { date, time })
+}
+
+/// The date a duration like `3d`, `2w`, `12h` or `1d12h` from `now`. Days and
+/// weeks give a date, hours and minutes a time as well.
+pub fn after(now: DateTime<FixedOffset>, duration: &str) -> Result<PostDate, Error> {
+    let invalid = || {
+        Error::from_string(
+            format!(
+                "'{}' isn't a duration like `3d`, `2w`, `12h` or `1d12h`",
+                duration
+            )
+            .as_str(),
+        )
+    };
+
+    let mut total = chrono::Duration::zero();
+    let mut has_time = false;
+    let mut number = String::new();
+    for c in duration.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let count = number.parse::<i64>().map_err(|_| invalid())?;
+        number.clear();
+        total += match c {
+            'w' => chrono::Duration::weeks(count),
+            'd' => chrono::Duration::days(count),
+            'h' => chrono::Duration::hours(count),
+            'm' => chrono::Duration::minutes(count),
+            _ => return Err(invalid()),
+        };
+        has_time |= matches!(c, 'h' | 'm');
+    }
+    if !number.is_empty() || total.is_zero() {
+        return Err(invalid());
+    }
+
+    let then = now + total;
+    Ok(PostDate {
+        date: then.date_naive(),
+        time: has_time.then(|| then.time()),
+    })
+}
+
+/// Splits the date at the start of a file name, like Jekyll's
+/// `2024-06-01-title.md`, from the rest of the name after it
+pub fn split_dated_name(name: &str) -> Option<(NaiveDate, &str)> {
+    let date = NaiveDate::parse_from_str(name.get(..10)?, "%Y-%m-%d").ok()?;
+    let rest = name.get(10..)?.strip_prefix('-')?;
+    Some((date, rest))
+}
+
+/// Reads a date from the front matter of a post, as written by the site
+/// generators or with `date_format`. Dates without a time are taken as
+/// midnight in `zone`.
+pub fn from_value(
+    value: &toml::Value,
+    zone: Zone,
+    date_format: Option<&str>,
+) -> Option<DateTime<FixedOffset>> {
+    let text = match value {
+        toml::Value::Datetime(datetime) => datetime.to_string(),
+        toml::Value::String(text) => text.trim().to_string(),
+        _ => return None,
+    };
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(&text) {
+        return Some(datetime);
+    }
+    for format in date_format.into_iter().chain(["%Y-%m-%d %H:%M:%S%z"]) {
+        if let Ok(datetime) = DateTime::parse_from_str(&text, format) {
+            return Some(datetime);
+        }
+    }
+    for format in date_format.into_iter().chain([
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ]) {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(&text, format) {
+            return Some(zone.at(datetime.date(), datetime.time()));
+        }
+    }
+    for format in date_format.into_iter().chain(["%Y-%m-%d"]) {
+        if let Ok(date) = NaiveDate::parse_from_str(&text, format) {
+            return Some(zone.at(date, NaiveTime::MIN));
+        }
+    }
+
+    None
+}
+
+// The first `weekday` after today
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days_ahead =
+        (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday() - 1) % 7 + 1;
+    today + Days::new(days_ahead as u64)
+}
+
+fn shift(date: NaiveDate, count: u32, unit: &str, forward: bool) -> Option<NaiveDate> {
+    let unit = unit.trim_end_matches('s');
+    match (unit, forward) {
+        ("day", true) => date.checked_add_days(Days::new(count.into())),
+        ("day", false) => date.checked_sub_days(Days::new(count.into())),
+        ("week", true) => date.checked_add_days(Days::new(7 * count as u64)),
+        ("week", false) => date.checked_sub_days(Days::new(7 * count as u64)),
+        ("month", true) => date.checked_add_months(Months::new(count)),
+        ("month", false) => date.checked_sub_months(Months::new(count)),
+        ("year", true) => date.checked_add_months(Months::new(12 * count)),
+        ("year", false) => date.checked_sub_months(Months::new(12 * count)),
+        _ => None,
+    }
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    // chrono takes both full and three letter names
+    word.parse().ok()
+}
+
+// `14:00`, `14:00:30`, `9am` or `9:30pm`
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    let (clock, offset) = match (word.strip_suffix("am"), word.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(0)),
+        (_, Some(clock)) => (clock, Some(12)),
+        _ => (word, None),
+    };
+
+    let mut parts = clock.split(':');
+    let hour = parts.next()?.parse::<u32>().ok()?;
+    let minute = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    let second = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if parts.next().is_some() || (offset.is_none() && !clock.contains(':')) {
+        return None;
+    }
+
+    let hour = match offset {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(offset) => hour % 12 + offset,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A Monday
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, 3)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn parse(input: &str) -> Option<(NaiveDate, Option<NaiveTime>)> {
+        let zone = Zone::Named(chrono_tz::UTC);
+        parse_relative_to(input, zone, now()).map(|d| (d.date, d.time))
+    }
+
+    #[test]
+    fn reads_days_relative_to_today() {
+        assert_eq!(parse("today"), Some((day(2024, 6, 3), None)));
+        assert_eq!(parse("Yesterday"), Some((day(2024, 6, 2), None)));
+        assert_eq!(parse("tomorrow"), Some((day(2024, 6, 4), None)));
+        assert_eq!(parse("now"), Some((day(2024, 6, 3), Some(time(12, 0)))));
+    }
+
+    #[test]
+    fn reads_weekdays() {
+        assert_eq!(parse("wednesday"), Some((day(2024, 6, 5), None)));
+        assert_eq!(parse("fri"), Some((day(2024, 6, 7), None)));
+        // Today's weekday is the one a week from now, or a week ago
+        assert_eq!(parse("monday"), Some((day(2024, 6, 10), None)));
+        assert_eq!(parse("next monday"), Some((day(2024, 6, 10), None)));
+        assert_eq!(parse("last monday"), Some((day(2024, 5, 27), None)));
+        assert_eq!(parse("next tuesday"), Some((day(2024, 6, 4), None)));
+        assert_eq!(parse("last sunday"), Some((day(2024, 6, 2), None)));
+        assert_eq!(parse("next week"), Some((day(2024, 6, 10), None)));
+        assert_eq!(parse("last week"), Some((day(2024, 5, 27), None)));
+    }
+
+    #[test]
+    fn reads_counted_units() {
+        assert_eq!(parse("in 3 days"), Some((day(2024, 6, 6), None)));
+        assert_eq!(parse("in 1 day"), Some((day(2024, 6, 4), None)));
+        assert_eq!(parse("2 weeks ago"), Some((day(2024, 5, 20), None)));
+        assert_eq!(parse("in 1 month"), Some((day(2024, 7, 3), None)));
+        assert_eq!(parse("2 years ago"), Some((day(2022, 6, 3), None)));
+        assert_eq!(parse("in 3 fortnights"), None);
+    }
+
+    #[test]
+    fn reads_times() {
+        assert_eq!(parse("9am"), Some((day(2024, 6, 3), Some(time(9, 0)))));
+        assert_eq!(parse("9:30pm"), Some((day(2024, 6, 3), Some(time(21, 30)))));
+        assert_eq!(parse("12am"), Some((day(2024, 6, 3), Some(time(0, 0)))));
+        assert_eq!(parse("12pm"), Some((day(2024, 6, 3), Some(time(12, 0)))));
+        assert_eq!(
+            parse("tomorrow at 14:00"),
+            Some((day(2024, 6, 4), Some(time(14, 0))))
+        );
+        assert_eq!(
+            parse("next friday 9:30"),
+            Some((day(2024, 6, 7), Some(time(9, 30))))
+        );
+        assert_eq!(parse("13pm"), None);
+        assert_eq!(parse("tomorrow 9"), None);
+    }
+
+    #[test]
+    fn reads_absolute_dates() {
+        assert_eq!(parse("2024-01-15"), Some((day(2024, 1, 15), None)));
+        assert_eq!(
+            parse("2024-01-15 14:00"),
+            Some((day(2024, 1, 15), Some(time(14, 0))))
+        );
+        assert_eq!(
+            parse("2024-01-15T08:30"),
+            Some((day(2024, 1, 15), Some(time(8, 30))))
+        );
+        assert_eq!(
+            parse("2024-01-15T08:30:00+02:00"),
+            Some((day(2024, 1, 15), Some(time(6, 30))))
+        );
+        assert_eq!(parse("someday"), None);
+    }
+
+    #[test]
+    fn reads_durations() {
+        let now = now().and_utc().fixed_offset();
+        let after = |duration| after(now, duration).ok().map(|d| (d.date, d.time));
+
+        assert_eq!(after("3d"), Some((day(2024, 6, 6), None)));
+        assert_eq!(after("2w"), Some((day(2024, 6, 17), None)));
+        assert_eq!(after("1d12h"), Some((day(2024, 6, 5), Some(time(0, 0)))));
+        assert_eq!(after("90m"), Some((day(2024, 6, 3), Some(time(13, 30)))));
+        assert_eq!(after("3"), None);
+        assert_eq!(after("3x"), None);
+        assert_eq!(after("0d"), None);
+        assert_eq!(after(""), None);
+    }
+
+    #[test]
+    fn reads_front_matter_dates() {
+        let zone = Zone::Named(chrono_tz::UTC);
+        let read =
+            |value: toml::Value, format| from_value(&value, zone, format).map(|d| d.naive_local());
+        let at = |date: NaiveDate, hour, minute| date.and_time(time(hour, minute));
+
+        assert_eq!(
+            read(toml::Value::String("2024-06-01".to_string()), None),
+            Some(at(day(2024, 6, 1), 0, 0))
+        );
+        assert_eq!(
+            read("2024-06-01T10:15:00+02:00".parse().unwrap(), None),
+            Some(at(day(2024, 6, 1), 10, 15))
+        );
+        assert_eq!(
+            read(
+                toml::Value::String("01.06.2024 10:15".to_string()),
+                Some("%d.%m.%Y %H:%M")
+            ),
+            Some(at(day(2024, 6, 1), 10, 15))
+        );
+        assert_eq!(read(toml::Value::Integer(1), None), None);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::collision::{with_free_suffix, BUNDLE_INDEX};
+use crate::editor;
+use crate::error::Error;
+use crate::project::Project;
+use crate::prompt;
+use crate::publish::move_to;
+use clap::Args;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directory in the project root deleted posts are moved to when there's no
+/// system trash
+pub const TRASH_DIRECTORY: &str = ".newpost-trash";
+
+/// Programs that move files to the system trash, and the arguments they take
+/// before the file
+#[cfg(target_os = "macos")]
+const TRASH_COMMANDS: [(&str, &[&str]); 1] = [("trash", &[])];
+#[cfg(not(target_os = "macos"))]
+const TRASH_COMMANDS: [(&str, &[&str]); 2] = [("gio", &["trash"]), ("trash-put", &[])];
+
+#[derive(Args, Debug)]
+pub struct DeleteArguments {
+    /// Post to delete, with its bundle if it's in one
+    post: PathBuf,
+
+    /// Delete the post for good instead of moving it to the trash
+    #[arg(long)]
+    permanent: bool,
+
+    /// Don't ask before deleting for good
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// Runs the `delete` subcommand, moving a post to the system trash, or to
+/// `.newpost-trash/` if there isn't one
+pub fn run(args: DeleteArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let path = current_dir.join(&args.post);
+    if !path.is_file() {
+        return Err(Error::from_string(
+            format!("There's no post at '{}'", path.display()).as_str(),
+        ));
+    }
+    let is_bundle = path.file_stem().is_some_and(|s| s == BUNDLE_INDEX);
+    let target = if is_bundle {
+        path.parent().unwrap_or(&path).to_path_buf()
+    } else {
+        path
+    };
+
+    if args.permanent {
+        let question = format!("Delete '{}' for good?", target.display());
+        if !args.yes && !prompt::confirm(&question, false)? {
+            return Err(Error::from_string("Aborted"));
+        }
+        let removed = if is_bundle {
+            fs::remove_dir_all(&target)
+        } else {
+            fs::remove_file(&target)
+        };
+        return removed.map_err(|e| {
+            Error::from_error(&format!("Failed to delete '{}'", target.display()), &e)
+        });
+    }
+
+    if move_to_system_trash(&target) {
+        println!("Moved '{}' to the trash", target.display());
+        return Ok(());
+    }
+
+    let relative = target.strip_prefix(&project.root).unwrap_or(&target);
+    let trashed = project.root.join(TRASH_DIRECTORY).join(relative);
+    let trashed = if trashed.exists() {
+        with_free_suffix(&trashed)
+    } else {
+        trashed
+    };
+    move_to(&target, &trashed)?;
+    println!("Moved '{}' to '{}'", target.display(), trashed.display());
+    Ok(())
+}
+
+// Whether one of the trash programs is installed and moved the file
+fn move_to_system_trash(path: &Path) -> bool {
+    TRASH_COMMANDS
+        .iter()
+        .filter(|(program, _)| editor::is_installed(program))
+        .any(|(program, args)| {
+            Command::new(program)
+                .args(*args)
+                .arg(path)
+                .status()
+                .is_ok_and(|s| s.success())
+        })
+}
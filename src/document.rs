@@ -0,0 +1,333 @@
+use crate::error::Error;
+use crate::front_matter::{org_prefix, rst_prefix, to_json, to_org, to_rst, to_yaml};
+use crate::post;
+use crate::preset::Preset;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use toml_edit::DocumentMut;
+
+/// An existing post whose front matter can be changed while leaving the rest
+/// of the file, and the formatting of the other fields, as it was
+pub struct Document {
+    path: PathBuf,
+    header: Header,
+    body: String,
+}
+
+enum Header {
+    Toml(DocumentMut),
+    Yaml(Vec<String>),
+    Json(serde_json::Map<String, serde_json::Value>),
+    Org(Vec<String>),
+    /// The title heading, and the field list after it
+    Rst(String, Vec<String>),
+}
+
+impl Document {
+    pub fn read(path: &Path) -> Result<Document, Error> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Error::from_error(&format!("Failed to read '{}'", path.display()), &e))?;
+
+        let (header, body) = split(&contents, post::is_rst(path))
+            .ok_or(post::front_matter_error(path, &contents))?;
+
+        Ok(Document {
+            path: path.to_path_buf(),
+            header,
+            body,
+        })
+    }
+
+    /// The front matter's current fields
+    pub fn table(&self) -> Table {
+        let is_rst = matches!(self.header, Header::Rst(_, _));
+        post::parse_front_matter(&self.to_string(), is_rst).unwrap_or_default()
+    }
+
+    /// Changes the value of a field where it is, or adds it after the field
+    /// named `after`, or at the end if there's no such field
+    pub fn set(&mut self, key: &str, value: Value, after: &str) {
+        match &mut self.header {
+            Header::Toml(document) => set_toml(document, key, value, after),
+            Header::Yaml(lines) => {
+                let lines_of_field = to_yaml(&single(key, value), 0)
+                    .lines()
+                    .map(String::from)
+                    .collect();
+                replace_lines(lines, lines_of_field, &yaml_field(key), &yaml_field(after))
+            }
+            Header::Json(map) => {
+                let value = to_json(&value);
+                match map.get_mut(key) {
+                    Some(existing) => *existing = value,
+                    None => {
+                        let position = map.keys().position(|k| k == after).map(|i| i + 1);
+                        let mut entries = std::mem::take(map).into_iter().collect::<Vec<_>>();
+                        entries.insert(position.unwrap_or(entries.len()), (key.to_string(), value));
+                        *map = entries.into_iter().collect();
+                    }
+                }
+            }
+            Header::Org(lines) => {
+                let lines_of_field = to_org(&single(key, value))
+                    .lines()
+                    .map(String::from)
+                    .collect();
+                replace_lines(lines, lines_of_field, &org_prefix(key), &org_prefix(after))
+            }
+            Header::Rst(heading, lines) if key == "title" => {
+                *heading = to_rst(&single(key, value))
+                    .lines()
+                    .take(2)
+                    .map(|l| format!("{}\n", l))
+                    .collect();
+            }
+            Header::Rst(_, lines) => {
+                let lines_of_field = to_rst(&single(key, value))
+                    .lines()
+                    .map(String::from)
+                    .collect();
+                replace_lines(lines, lines_of_field, &rst_prefix(key), &rst_prefix(after))
+            }
+        }
+    }
+
+    /// Changes or adds a custom field, in the `[extra]` table of TOML front
+    /// matter and at the end of the other formats
+    pub fn set_extra(&mut self, key: &str, value: Value) {
+        match &mut self.header {
+            Header::Toml(document) if !document.contains_key(key) => {
+                let extra = document
+                    .entry("extra")
+                    .or_insert(toml_edit::table())
+                    .as_table_like_mut();
+                match extra {
+                    Some(extra) => {
+                        extra.insert(key, toml_edit::Item::Value(to_toml_edit(&value)));
+                    }
+                    None => set_toml(document, key, value, ""),
+                }
+            }
+            Header::Toml(_)
+            | Header::Yaml(_)
+            | Header::Json(_)
+            | Header::Org(_)
+            | Header::Rst(_, _) => self.set(key, value, ""),
+        }
+    }
+
+    /// Changes or adds a custom field where the site generator keeps them, in
+    /// `[extra]` for Zola and when it isn't known, and next to the others
+    /// otherwise
+    pub fn set_custom(&mut self, key: &str, value: Value, preset: Option<Preset>) {
+        if preset.is_none_or(|p| p.nests_extra()) {
+            self.set_extra(key, value);
+        } else {
+            self.set(key, value, "");
+        }
+    }
+
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    pub fn set_body(&mut self, body: String) {
+        self.body = body;
+    }
+
+    /// Changes where the post is written to, after it's been moved
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    pub fn write(&self) -> Result<(), Error> {
+        fs::write(&self.path, self.to_string()).map_err(|e| {
+            Error::from_error(&format!("Failed to write '{}'", self.path.display()), &e)
+        })
+    }
+}
+
+impl std::fmt::Display for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.header {
+            Header::Toml(document) => write!(f, "+++\n{}+++\n", document)?,
+            Header::Yaml(lines) => write!(f, "---\n{}---\n", join_lines(lines))?,
+            Header::Json(map) => write!(
+                f,
+                "{}",
+                serde_json::to_string_pretty(map).map_err(|_| std::fmt::Error)?
+            )?,
+            Header::Org(lines) => write!(f, "{}", join_lines(lines))?,
+            Header::Rst(heading, lines) if heading.is_empty() => {
+                write!(f, "{}", join_lines(lines))?
+            }
+            Header::Rst(heading, lines) => write!(f, "{}\n{}", heading, join_lines(lines))?,
+        }
+
+        write!(f, "{}", self.body)
+    }
+}
+
+// Splits a post into its front matter and the rest, the front matter's
+// delimiters are left out of both. Only RST posts may have it unmarked.
+fn split(contents: &str, is_rst: bool) -> Option<(Header, String)> {
+    let contents = contents.trim_start_matches('\u{feff}');
+
+    if let Some((toml, body)) = between(contents, "+++") {
+        Some((Header::Toml(toml.parse().ok()?), body.to_string()))
+    } else if let Some((yaml, body)) = between(contents, "---") {
+        let lines = yaml.lines().map(String::from).collect();
+        Some((Header::Yaml(lines), body.to_string()))
+    } else if contents.starts_with('{') {
+        let mut stream =
+            serde_json::Deserializer::from_str(contents).into_iter::<serde_json::Value>();
+        let map = match stream.next()?.ok()? {
+            serde_json::Value::Object(map) => map,
+            _ => return None,
+        };
+        Some((
+            Header::Json(map),
+            contents[stream.byte_offset()..].to_string(),
+        ))
+    } else if contents.starts_with("#+") {
+        let lines = contents
+            .lines()
+            .take_while(|l| l.starts_with("#+"))
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let header_length = lines.iter().map(|l| l.len() + 1).sum::<usize>();
+        let body = contents.get(header_length..).unwrap_or_default();
+        Some((Header::Org(lines), body.to_string()))
+    } else if !is_rst {
+        None
+    } else {
+        let mut rest = contents.trim_start_matches('\n');
+        let mut heading = String::new();
+        if !rest.starts_with(':') {
+            for _ in 0..2 {
+                let (line, after) = rest.split_once('\n')?;
+                heading.push_str(line);
+                heading.push('\n');
+                rest = after;
+            }
+            rest = rest.trim_start_matches('\n');
+        }
+
+        let mut lines = Vec::new();
+        while rest.starts_with(':') {
+            let (line, after) = rest.split_once('\n').unwrap_or((rest, ""));
+            lines.push(line.to_string());
+            rest = after;
+        }
+        Some((Header::Rst(heading, lines), rest.to_string()))
+    }
+}
+
+// The block between the delimiter on the first line and the next line with
+// it, and everything after that
+pub fn between<'a>(contents: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let rest = contents.strip_prefix(delimiter)?.trim_start_matches(' ');
+    let rest = rest
+        .strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == delimiter {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+fn single(key: &str, value: Value) -> Table {
+    Table::from_iter([(key.to_string(), value)])
+}
+
+fn join_lines(lines: &[String]) -> String {
+    lines.iter().map(|l| format!("{}\n", l)).collect()
+}
+
+fn yaml_field(key: &str) -> String {
+    format!("{}:", key)
+}
+
+// The lines of the field starting with `prefix`, including the list items
+// and nested fields below it
+fn field_lines(lines: &[String], prefix: &str) -> Option<std::ops::Range<usize>> {
+    let start = lines
+        .iter()
+        .position(|l| l == prefix || l.starts_with(&format!("{} ", prefix)))?;
+    let length = lines[start + 1..]
+        .iter()
+        .take_while(|l| l.starts_with([' ', '\t', '-']) && !l.starts_with("---"))
+        .count();
+
+    Some(start..start + 1 + length)
+}
+
+fn replace_lines(lines: &mut Vec<String>, new_lines: Vec<String>, prefix: &str, after: &str) {
+    let range = match field_lines(lines, prefix) {
+        Some(range) => range,
+        None => {
+            let end = field_lines(lines, after).map_or(lines.len(), |r| r.end);
+            end..end
+        }
+    };
+
+    lines.splice(range, new_lines);
+}
+
+fn set_toml(document: &mut DocumentMut, key: &str, value: Value, after: &str) {
+    let value = to_toml_edit(&value);
+    let root = document.as_table_mut();
+
+    // Zola keeps taxonomies and custom fields in their own tables
+    for table in ["extra", "taxonomies"] {
+        if let Some(existing) = root
+            .get_mut(table)
+            .and_then(|t| t.as_table_like_mut())
+            .and_then(|t| t.get_mut(key))
+        {
+            *existing = toml_edit::Item::Value(value);
+            return;
+        }
+    }
+
+    if let Some(existing) = root.get_mut(key).and_then(|i| i.as_value_mut()) {
+        let decor = existing.decor().clone();
+        *existing = value;
+        *existing.decor_mut() = decor;
+        return;
+    }
+
+    // Reinserts every field to put the new one in place
+    let keys = root.iter().map(|(k, _)| k.to_string()).collect::<Vec<_>>();
+    let entries = keys
+        .iter()
+        .filter_map(|k| root.remove_entry(k))
+        .collect::<Vec<_>>();
+    let mut value = Some(value);
+    for (k, item) in entries {
+        let is_anchor = k.get() == after;
+        root.insert_formatted(&k, item);
+        if is_anchor {
+            if let Some(value) = value.take() {
+                root.insert(key, toml_edit::Item::Value(value));
+            }
+        }
+    }
+    if let Some(value) = value {
+        root.insert(key, toml_edit::Item::Value(value));
+    }
+}
+
+fn to_toml_edit(value: &Value) -> toml_edit::Value {
+    value
+        .to_string()
+        .parse()
+        .unwrap_or_else(|_| toml_edit::Value::from(value.to_string()))
+}
@@ -0,0 +1,362 @@
+use crate::document::Document;
+use crate::error::Error;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::{env, fs};
+
+/// What new posts are opened with
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenWith {
+    /// The editor from `--editor`, the config or the environment
+    #[default]
+    Editor,
+    /// The application the system opens the file type with
+    System,
+}
+
+/// Terminal pane the editor is opened in, instead of the current terminal
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Pane {
+    /// Whichever of the others the command is run in
+    Auto,
+    /// A new pane next to the current one in tmux
+    Tmux,
+    /// A new window in tmux
+    TmuxWindow,
+    /// A new pane next to the current one in WezTerm
+    Wezterm,
+    /// A new window in the current kitty tab, with remote control enabled
+    Kitty,
+}
+
+impl Pane {
+    /// The multiplexer the command runs in, from the variables they set
+    pub fn detect() -> Option<Pane> {
+        let is_set = |name: &str| env::var_os(name).is_some_and(|v| !v.is_empty());
+        if is_set("TMUX") {
+            Some(Pane::Tmux)
+        } else if is_set("WEZTERM_PANE") {
+            Some(Pane::Wezterm)
+        } else if is_set("KITTY_WINDOW_ID") {
+            Some(Pane::Kitty)
+        } else {
+            None
+        }
+    }
+
+    /// The pane to use, with `auto` replaced by the detected one
+    pub fn resolve(self) -> Result<Pane, Error> {
+        match self {
+            Pane::Auto => Pane::detect().ok_or(Error::from_string(
+                "Not running in tmux, WezTerm or kitty to open a pane in",
+            )),
+            Pane::Tmux | Pane::TmuxWindow | Pane::Wezterm | Pane::Kitty => Ok(self),
+        }
+    }
+
+    // The command that runs the editor in a new pane
+    fn command(&self) -> Vec<&'static str> {
+        match self {
+            Pane::Auto | Pane::Tmux => vec!["tmux", "split-window", "-h"],
+            Pane::TmuxWindow => vec!["tmux", "new-window"],
+            Pane::Wezterm => vec!["wezterm", "cli", "split-pane", "--right", "--"],
+            Pane::Kitty => vec!["kitty", "@", "launch", "--type=window", "--cwd=current"],
+        }
+    }
+}
+
+/// How a post is opened once it's there
+pub enum Opener {
+    Editor {
+        command: String,
+        detach: bool,
+        pane: Option<Pane>,
+    },
+    System,
+    /// Only print the post's path
+    Print,
+}
+
+impl Opener {
+    /// Whether opening the post returns only once it's been closed
+    pub fn waits(&self) -> bool {
+        match self {
+            Opener::Editor { detach, pane, .. } => !detach && pane.is_none(),
+            Opener::System | Opener::Print => false,
+        }
+    }
+
+    pub fn open(self, file_path: &Path) -> Result<(), Error> {
+        match self {
+            Opener::Editor {
+                command,
+                detach,
+                pane,
+            } => run(command, file_path, detach, pane),
+            Opener::System => open_with_system(file_path),
+            Opener::Print => {
+                println!("{}", file_path.display());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Editors tried when none is set, in order
+#[cfg(windows)]
+pub const FALLBACK_EDITORS: [&str; 1] = ["notepad"];
+#[cfg(target_os = "macos")]
+pub const FALLBACK_EDITORS: [&str; 3] = ["nano", "vi", "open"];
+#[cfg(not(any(windows, target_os = "macos")))]
+pub const FALLBACK_EDITORS: [&str; 3] = ["nano", "vi", "xdg-open"];
+
+/// Commands that hand files to the system's default application and return
+/// right away, rather than editing them
+pub const SYSTEM_OPENERS: [&str; 3] = ["xdg-open", "open", "start"];
+
+/// Whether the editor is one of the [`SYSTEM_OPENERS`]
+pub fn is_system_opener(command: &str) -> bool {
+    SYSTEM_OPENERS.contains(&command.trim())
+}
+
+/// The editor to open posts with, from `--editor`, the config, `VISUAL` or
+/// `EDITOR`, or else the first of the platform's usual editors that's installed
+pub fn command_string(editor_path: Option<String>) -> Result<String, Error> {
+    if let Some(cmd) = editor_path {
+        return Ok(cmd);
+    }
+
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .or_else(|| {
+            FALLBACK_EDITORS
+                .into_iter()
+                .find(|e| is_installed(e))
+                .map(String::from)
+        })
+        .ok_or(Error::from_string(
+            "Unable to find an editor, set one with --editor, `editor` in the config or $EDITOR",
+        ))
+}
+
+/// Hands the file to the system's default application for it, with
+/// `xdg-open`, `open` or `start`
+pub fn open_with_system(file_path: &Path) -> Result<(), Error> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // The empty argument is the window title `start` takes first
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = command
+        .arg(file_path)
+        .status()
+        .map_err(|e| Error::from_error("Failed to open the post", &e))?;
+    if !status.success() {
+        return Err(Error::from_string(
+            "The system couldn't open the post, set an editor with --editor",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether the program can be found on the `PATH`
+pub fn is_installed(program: &str) -> bool {
+    find_program(program).is_some()
+}
+
+// The program's file in a directory on the `PATH`, on Windows also with the
+// extensions in `PATHEXT`, like `code.cmd`
+fn find_program(program: &str) -> Option<PathBuf> {
+    let extensions = if cfg!(windows) {
+        env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+    } else {
+        String::new()
+    };
+    let candidates = |dir: &Path| {
+        let mut candidates = vec![dir.join(program)];
+        for extension in extensions.split(';').filter(|e| !e.is_empty()) {
+            candidates.push(dir.join(format!("{}{}", program, extension.to_lowercase())));
+        }
+        candidates
+    };
+
+    if Path::new(program).components().count() > 1 {
+        return candidates(Path::new("")).into_iter().find(|p| p.is_file());
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|dir| candidates(&dir))
+        .find(|p| p.is_file())
+}
+
+// The command that starts the program. Windows only finds `.exe` files by their
+// name, so the others are looked up in the `PATH`, and the rest, like `start`,
+// are left to `cmd`.
+fn program_command(program: &str) -> Command {
+    if !cfg!(windows) {
+        return Command::new(program);
+    }
+
+    match find_program(program) {
+        Some(path) => Command::new(path),
+        None => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", program]);
+            command
+        }
+    }
+}
+
+// Splits the editor into its arguments, like a shell would. Windows paths have
+// backslashes, so there only double quotes group arguments.
+fn split(editor: &str) -> Result<Vec<String>, Error> {
+    if !cfg!(windows) {
+        return shell_words::split(editor)
+            .map_err(|e| Error::from_error(&format!("Failed to parse editor '{}'", editor), &e));
+    }
+
+    let mut arguments = Vec::new();
+    let mut argument = None::<String>;
+    let mut quoted = false;
+    for c in editor.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                argument.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => arguments.extend(argument.take()),
+            c => argument.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return Err(Error::from_string(
+            format!("Failed to parse editor '{}': missing closing quote", editor).as_str(),
+        ));
+    }
+    arguments.extend(argument);
+
+    Ok(arguments)
+}
+
+/// Opens the file in the editor and waits for it to close, unless `detach` is
+/// set or it's opened in a new terminal `pane`. The editor is split into
+/// arguments like a shell would, so quoted paths and arguments with spaces work.
+///
+/// `{path}` (or `{}`) in the editor is replaced with the file's path, which is
+/// otherwise added at the end, and `{line}` with the line after the front
+/// matter. Without placeholders, editors known to take a line to start on open
+/// the file with the cursor on that line.
+pub fn run(
+    editor: String,
+    file_path: &Path,
+    detach: bool,
+    pane: Option<Pane>,
+) -> Result<(), Error> {
+    let editor_args = split(&editor)?;
+    if editor_args.is_empty() {
+        return Err(Error::from_string("The editor command is empty"));
+    }
+
+    let has_path = editor_args
+        .iter()
+        .any(|a| a.contains("{path}") || a.contains("{}"));
+    let has_line = editor_args.iter().any(|a| a.contains("{line}"));
+    let path = file_path.to_string_lossy();
+    let line = first_body_line(file_path);
+    let mut editor_args = editor_args
+        .into_iter()
+        .map(|a| {
+            a.replace("{path}", &path)
+                .replace("{}", &path)
+                .replace("{line}", &line.to_string())
+        })
+        .collect::<Vec<_>>();
+    match (has_path, has_line) {
+        (true, _) => {}
+        (false, true) => editor_args.push(path.to_string()),
+        (false, false) => {
+            let arguments = path_arguments(&editor_args[0], &path, line);
+            editor_args.extend(arguments)
+        }
+    }
+
+    if let Some(pane) = pane {
+        let mut pane_args = pane.command();
+        let mut command = program_command(pane_args.remove(0));
+        let status = command
+            .args(pane_args)
+            .args(&editor_args)
+            .status()
+            .map_err(|e| Error::from_error("Failed to open a pane for the editor", &e))?;
+        if !status.success() {
+            return Err(Error::from_string("Failed to open a pane for the editor"));
+        }
+        return Ok(());
+    }
+
+    let mut command = program_command(&editor_args[0]);
+    command.args(&editor_args[1..]);
+
+    if detach {
+        // Keeps the editor from writing over the terminal it's left running in
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| Error::from_error("Failed to start editor process", &e))?;
+    if detach {
+        return Ok(());
+    }
+
+    child
+        .wait()
+        .map_err(|e| Error::from_error("Error occured during editor run time", &e))
+        .map(|_| ())
+}
+
+// The arguments that open the file at the line, for the editors that are known
+// to take one
+fn path_arguments(program: &str, path: &str, line: usize) -> Vec<String> {
+    let name = Path::new(program)
+        .file_stem()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match name.as_str() {
+        "vi" | "vim" | "nvim" | "gvim" | "mvim" | "nano" | "emacs" | "emacsclient" | "micro"
+        | "kak" | "joe" | "mg" => vec![format!("+{}", line), path.to_string()],
+        "code" | "code-insiders" | "codium" | "cursor" => {
+            vec!["--goto".to_string(), format!("{}:{}", path, line)]
+        }
+        "subl" | "zed" | "hx" | "helix" => vec![format!("{}:{}", path, line)],
+        _ => vec![path.to_string()],
+    }
+}
+
+// The number of the first line after the front matter, or the first line if
+// the front matter can't be read
+fn first_body_line(file_path: &Path) -> usize {
+    let (Ok(contents), Ok(document)) = (fs::read_to_string(file_path), Document::read(file_path))
+    else {
+        return 1;
+    };
+
+    let header = &contents[..contents.len().saturating_sub(document.body().len())];
+    header.lines().count() + 1
+}
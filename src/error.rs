@@ -0,0 +1,26 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub struct Error {
+    message: String,
+}
+
+impl Error {
+    pub fn from_error(message: &str, error: &dyn Display) -> Self {
+        Error {
+            message: format!("{}: {}", message, error),
+        }
+    }
+
+    pub fn from_string(message: &str) -> Self {
+        Error {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
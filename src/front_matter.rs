@@ -0,0 +1,478 @@
+use crate::error::Error;
+use crate::preset::{nest_taxonomies, Preset};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use toml::value::{Date, Datetime};
+use toml::{Table, Value};
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// TOML front matter delimited by `+++` (Zola)
+    #[default]
+    Toml,
+    /// YAML front matter delimited by `---` (Jekyll, Hugo, ...)
+    Yaml,
+    /// JSON object front matter (Hugo, Eleventy)
+    Json,
+}
+
+impl Format {
+    /// The format of the front matter a post or template starts with
+    pub fn detect(contents: &str) -> Option<Format> {
+        let contents = contents.trim_start_matches('\u{feff}');
+        if contents.starts_with("+++") {
+            Some(Format::Toml)
+        } else if contents.starts_with("---") {
+            Some(Format::Yaml)
+        } else if contents.starts_with('{') {
+            Some(Format::Json)
+        } else {
+            None
+        }
+    }
+
+    pub fn render(&self, table: &Table) -> Result<String, Error> {
+        match self {
+            Format::Toml => toml::to_string(table)
+                .map(|toml| format!("+++\n{}+++\n", toml))
+                .map_err(|e| Error::from_error("Failed to serialize front matter", &e)),
+            Format::Yaml => Ok(format!("---\n{}---\n", to_yaml(table, 0))),
+            Format::Json => serde_json::to_string_pretty(&to_json(&Value::Table(table.clone())))
+                .map(|json| format!("{}\n", json))
+                .map_err(|e| Error::from_error("Failed to serialize front matter", &e)),
+        }
+    }
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Extension {
+    /// Markdown with a front matter block
+    #[default]
+    Md,
+    /// reStructuredText with a Pelican style metadata field list
+    Rst,
+    /// Org-mode with `#+KEYWORD` headers, as read by ox-hugo
+    Org,
+}
+
+impl Extension {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Extension::Md => "md",
+            Extension::Rst => "rst",
+            Extension::Org => "org",
+        }
+    }
+
+    /// Renders the metadata header of a new file, `format` only applies to
+    /// Markdown front matter
+    pub fn render(&self, table: &Table, format: Format) -> Result<String, Error> {
+        match self {
+            Extension::Md => format.render(table),
+            Extension::Rst => Ok(to_rst(table)),
+            Extension::Org => Ok(to_org(table)),
+        }
+    }
+}
+
+/// Kind of stable identifier to give new posts
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdKind {
+    /// Time ordered UUID (version 7)
+    Uuid,
+    /// 12 random hexadecimal characters
+    Short,
+}
+
+impl IdKind {
+    pub fn generate(&self) -> String {
+        let uuid = uuid::Uuid::now_v7();
+        match self {
+            IdKind::Uuid => uuid.hyphenated().to_string(),
+            // The end of a version 7 UUID is random, the start is the time
+            IdKind::Short => {
+                let uuid = uuid.simple().to_string();
+                uuid[uuid.len() - 12..].to_string()
+            }
+        }
+    }
+}
+
+/// Taxonomies of a post unless configured otherwise
+pub const DEFAULT_TAXONOMIES: [&str; 2] = ["tags", "categories"];
+
+/// Generator agnostic front matter, see [`Preset::arrange`] for how the keys
+/// are laid out for a specific site generator
+#[derive(Serialize, Debug)]
+pub struct FrontMatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    pub date: Datetime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<Datetime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub draft: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<i64>,
+    /// Old URLs of the post that should redirect to it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    /// Which taxonomies posts have, when configured. Those without terms are
+    /// written as empty lists and tags or categories not listed are left out.
+    #[serde(skip)]
+    pub taxonomies: Option<Vec<String>>,
+    /// strftime style format to write dates with, instead of as TOML dates
+    #[serde(skip)]
+    pub date_format: Option<String>,
+    /// Site specific fields, see [`flatten_extra`]
+    #[serde(skip_serializing_if = "Table::is_empty")]
+    pub extra: Table,
+}
+
+impl FrontMatter {
+    pub fn new(title: &str, date: Datetime, tags: Vec<String>) -> Self {
+        FrontMatter {
+            layout: None,
+            title: title.to_string(),
+            description: None,
+            slug: None,
+            date,
+            updated: None,
+            draft: None,
+            permalink: None,
+            weight: None,
+            aliases: Vec::new(),
+            authors: Vec::new(),
+            tags,
+            categories: Vec::new(),
+            taxonomies: None,
+            date_format: None,
+            extra: Table::new(),
+        }
+    }
+
+    /// Lays out the front matter keys the way the site generator expects them
+    pub fn to_table(&self, format: Format, preset: Option<Preset>) -> Result<Table, Error> {
+        // `Table::try_from` doesn't recognise datetimes, so go through the document
+        // serializer to get a table with proper `Value::Datetime` entries
+        let mut table = toml::to_string(self)
+            .map_err(|e| Error::from_error("Failed to serialize front matter", &e))
+            .and_then(|toml| {
+                toml.parse::<Table>()
+                    .map_err(|e| Error::from_error("Failed to serialize front matter", &e))
+            })?;
+
+        if let Some(date_format) = &self.date_format {
+            for (_, value) in table.iter_mut() {
+                if let Value::Datetime(datetime) = value {
+                    *value = Value::String(format_date(datetime, date_format)?);
+                }
+            }
+        }
+
+        let taxonomies = match &self.taxonomies {
+            Some(taxonomies) => {
+                table.retain(|k, _| {
+                    !DEFAULT_TAXONOMIES.contains(&k) || taxonomies.iter().any(|t| t == k)
+                });
+                for taxonomy in taxonomies {
+                    // Extra fields named after a taxonomy, like `series`, are its terms
+                    let terms = match table
+                        .get_mut("extra")
+                        .and_then(Value::as_table_mut)
+                        .and_then(|extra| extra.remove(taxonomy))
+                    {
+                        Some(Value::Array(terms)) => terms,
+                        Some(term) => vec![term],
+                        None => Vec::new(),
+                    };
+                    table.entry(taxonomy).or_insert(Value::Array(terms));
+                }
+                if table
+                    .get("extra")
+                    .and_then(Value::as_table)
+                    .is_some_and(Table::is_empty)
+                {
+                    table.remove("extra");
+                }
+                taxonomies.clone()
+            }
+            None => DEFAULT_TAXONOMIES.map(String::from).to_vec(),
+        };
+
+        match preset {
+            Some(preset) => preset.arrange(&mut table, &taxonomies),
+            // Without a preset TOML means Zola, while the YAML and JSON flavoured
+            // generators expect tags at the top level
+            None if format == Format::Toml => nest_taxonomies(&mut table, &taxonomies),
+            None => flatten_extra(&mut table),
+        }
+
+        Ok(table)
+    }
+}
+
+/// Moves the fields in `extra` to the top level, for generators that read
+/// custom fields from there rather than from an `[extra]` table like Zola
+pub fn flatten_extra(table: &mut Table) {
+    if let Some(Value::Table(extra)) = table.remove("extra") {
+        for (key, value) in extra {
+            table.entry(key).or_insert(value);
+        }
+    }
+}
+
+/// Reads a `key=value` field given on the command line, the value is read as
+/// TOML if possible and as a string otherwise
+pub fn parse_field(field: &str) -> Result<(String, Value), String> {
+    let (key, value) = field
+        .split_once('=')
+        .filter(|(k, _)| !k.trim().is_empty())
+        .ok_or(format!("expected `key=value`, got '{}'", field))?;
+
+    Ok((key.trim().to_string(), crate::config::parse_setting(value)))
+}
+
+/// Keeps only the listed fields, in the listed order. Fields that haven't been
+/// filled in get an empty value.
+pub fn select_fields(table: &mut Table, fields: &[String]) {
+    let date = table.get("date").cloned();
+
+    *table = fields
+        .iter()
+        .map(|field| {
+            let value = table.remove(field).unwrap_or_else(|| match field.as_str() {
+                "draft" => Value::Boolean(false),
+                "tags" | "categories" | "authors" | "aliases" => Value::Array(Vec::new()),
+                "taxonomies" | "extra" => Value::Table(Table::new()),
+                "updated" | "lastmod" => date.clone().unwrap_or(Value::String(String::new())),
+                _ => Value::String(String::new()),
+            });
+            (field.clone(), value)
+        })
+        .collect();
+}
+
+/// Writes a date with a strftime style format, dates without a time are taken
+/// as midnight UTC
+pub fn format_date(datetime: &Datetime, format: &str) -> Result<String, Error> {
+    let text = datetime.to_string();
+    let datetime = DateTime::parse_from_rfc3339(&text)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN).and_utc().fixed_offset())
+        })
+        .ok_or(Error::from_string(
+            format!("Failed to read the date '{}'", text).as_str(),
+        ))?;
+
+    let mut formatted = String::new();
+    write!(formatted, "{}", datetime.format(format))
+        .map_err(|_| Error::from_string(format!("Invalid date format '{}'", format).as_str()))?;
+    Ok(formatted)
+}
+
+pub fn to_toml_date(date: NaiveDate) -> Datetime {
+    Datetime {
+        date: Some(Date {
+            year: date.year() as u16,
+            month: date.month() as u8,
+            day: date.day() as u8,
+        }),
+        time: None,
+        offset: None,
+    }
+}
+
+pub fn to_toml_datetime(datetime: DateTime<FixedOffset>) -> Datetime {
+    datetime
+        .format("%Y-%m-%dT%H:%M:%S%:z")
+        .to_string()
+        .parse()
+        .expect("RFC 3339 timestamp is a valid TOML datetime")
+}
+
+pub fn to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(s) => serde_json::Value::from(s.as_str()),
+        Value::Integer(i) => serde_json::Value::from(*i),
+        Value::Float(f) => serde_json::Value::from(*f),
+        Value::Boolean(b) => serde_json::Value::from(*b),
+        Value::Datetime(d) => serde_json::Value::from(d.to_string()),
+        Value::Array(a) => serde_json::Value::Array(a.iter().map(to_json).collect()),
+        Value::Table(t) => {
+            serde_json::Value::Object(t.iter().map(|(k, v)| (k.clone(), to_json(v))).collect())
+        }
+    }
+}
+
+pub fn to_rst(table: &Table) -> String {
+    let mut rst = String::new();
+
+    if let Some(title) = table.get("title").and_then(|t| t.as_str()) {
+        rst.push_str(&format!(
+            "{}\n{}\n\n",
+            title,
+            "#".repeat(title.chars().count())
+        ));
+    }
+
+    // Nested tables, like Zola's taxonomies, have no equivalent so their
+    // fields are listed alongside the others
+    let fields = table.iter().flat_map(|(key, value)| match value {
+        Value::Table(t) => t.iter().collect::<Vec<_>>(),
+        _ => vec![(key, value)],
+    });
+
+    for (key, value) in fields {
+        let field = match (key.as_str(), value) {
+            ("title", _) => continue,
+            ("draft", Value::Boolean(true)) => format!("{} draft", rst_prefix(key)),
+            ("draft", _) => continue,
+            _ => format!("{} {}", rst_prefix(key), to_plain_text(value)),
+        };
+        rst.push_str(&field);
+        rst.push('\n');
+    }
+
+    rst
+}
+
+/// Start of the field list line `key` is written to in reStructuredText
+pub fn rst_prefix(key: &str) -> String {
+    match key {
+        "draft" => ":status:".to_string(),
+        "updated" => ":modified:".to_string(),
+        _ => format!(":{}:", key),
+    }
+}
+
+fn to_plain_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.replace('\n', " "),
+        Value::Array(a) => a.iter().map(to_plain_text).collect::<Vec<_>>().join(", "),
+        _ => value.to_string(),
+    }
+}
+
+pub fn to_org(table: &Table) -> String {
+    let fields = table.iter().flat_map(|(key, value)| match value {
+        Value::Table(t) => t.iter().collect::<Vec<_>>(),
+        _ => vec![(key, value)],
+    });
+
+    let mut org = String::new();
+    for (key, value) in fields {
+        let value = match (key.as_str(), value) {
+            (_, Value::Array(a)) if a.is_empty() => continue,
+            ("tags", Value::Array(tags)) => format!(
+                ":{}:",
+                tags.iter()
+                    // Org tags can't contain spaces
+                    .map(|t| to_plain_text(t).replace(' ', "_"))
+                    .collect::<Vec<_>>()
+                    .join(":")
+            ),
+            (_, Value::Array(a)) => a.iter().map(to_plain_text).collect::<Vec<_>>().join(" "),
+            _ => to_plain_text(value),
+        };
+        let keyword = format!("{} {}", org_prefix(key), value);
+        org.push_str(&keyword);
+        org.push('\n');
+    }
+
+    org
+}
+
+/// Start of the `#+KEYWORD` line `key` is written to in Org-mode
+pub fn org_prefix(key: &str) -> String {
+    match key {
+        "title" => "#+TITLE:".to_string(),
+        "date" => "#+DATE:".to_string(),
+        "tags" => "#+FILETAGS:".to_string(),
+        "updated" | "lastmod" => "#+HUGO_LASTMOD:".to_string(),
+        "draft" | "categories" | "layout" => format!("#+HUGO_{}:", key.to_uppercase()),
+        _ => format!("#+HUGO_CUSTOM_FRONT_MATTER: :{}", key),
+    }
+}
+
+pub fn to_yaml(table: &Table, indent: usize) -> String {
+    let padding = " ".repeat(indent);
+    let mut yaml = String::new();
+
+    for (key, value) in table {
+        match value {
+            Value::Table(t) if !t.is_empty() => {
+                yaml.push_str(&format!("{}{}:\n", padding, key));
+                yaml.push_str(&to_yaml(t, indent + 2));
+            }
+            Value::Array(a) if !a.is_empty() && !a.iter().any(|v| v.is_table() || v.is_array()) => {
+                yaml.push_str(&format!("{}{}:\n", padding, key));
+                for item in a {
+                    yaml.push_str(&format!("{}- {}\n", padding, to_yaml_scalar(item)));
+                }
+            }
+            _ => yaml.push_str(&format!("{}{}: {}\n", padding, key, to_yaml_scalar(value))),
+        }
+    }
+
+    yaml
+}
+
+fn to_yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) if is_plain_yaml(s) => s.clone(),
+        Value::Datetime(d) => d.to_string(),
+        Value::Array(a) => format!(
+            "[{}]",
+            a.iter().map(to_yaml_scalar).collect::<Vec<_>>().join(", ")
+        ),
+        // JSON strings and objects are valid YAML, and take care of escaping
+        _ => to_json(value).to_string(),
+    }
+}
+
+// Whether the string can be written without quotes and still be read back as
+// the same string. Errs on the side of quoting, except for timestamps which
+// are left bare so generators pick them up as dates.
+fn is_plain_yaml(s: &str) -> bool {
+    const RESERVED: [&str; 11] = [
+        "true", "false", "yes", "no", "on", "off", "y", "n", "null", "~", "",
+    ];
+
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    let looks_like_date = s.len() >= 10
+        && s.bytes().take(10).enumerate().all(|(i, b)| match i {
+            4 | 7 => b == b'-',
+            _ => b.is_ascii_digit(),
+        });
+
+    (first.is_alphabetic() || looks_like_date)
+        && !s.ends_with([' ', ':'])
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && s.chars()
+            .all(|c| c.is_alphanumeric() || " -_./:+()'!?&".contains(c))
+        && !RESERVED.contains(&s.to_lowercase().as_str())
+}
@@ -0,0 +1,64 @@
+use crate::error::Error;
+use std::path::Path;
+use std::process::Command;
+
+/// The user's name from the git config, as seen from `dir`
+pub fn user_name(dir: &Path) -> Option<String> {
+    config_value(dir, "user.name")
+}
+
+/// The branch checked out in the repository at `dir`
+pub fn branch(dir: &Path) -> Option<String> {
+    output(dir, &["rev-parse", "--abbrev-ref", "HEAD"])
+}
+
+/// The URL of the `origin` remote of the repository at `dir`
+pub fn remote_url(dir: &Path) -> Option<String> {
+    config_value(dir, "remote.origin.url")
+}
+
+/// The most recent tag reachable from `HEAD` in the repository at `dir`
+pub fn last_tag(dir: &Path) -> Option<String> {
+    output(dir, &["describe", "--tags", "--abbrev=0"])
+}
+
+/// The subjects of the commits after `since`, or all of them, newest first
+pub fn commit_subjects(dir: &Path, since: Option<&str>) -> Result<Vec<String>, Error> {
+    let range = since.map_or("HEAD".to_string(), |tag| format!("{}..HEAD", tag));
+    let output = Command::new("git")
+        .args(["log", "--no-merges", "--format=%s", &range])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| Error::from_error("Failed to run git", &e))?;
+    if !output.status.success() {
+        return Err(Error::from_string(
+            format!(
+                "Failed to read the commits in '{}': {}",
+                dir.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .as_str(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+fn config_value(dir: &Path, key: &str) -> Option<String> {
+    output(dir, &["config", "--get", key])
+}
+
+// What git prints, if it succeeds and prints anything
+fn output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string()).filter(|v| !v.is_empty())
+}
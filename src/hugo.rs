@@ -0,0 +1,125 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory Hugo's archetypes, its templates for new content, are in
+pub const ARCHETYPES_DIRECTORY: &str = "archetypes";
+
+const CONFIG_FILES: [&str; 4] = [
+    "hugo",
+    "config",
+    "config/_default/hugo",
+    "config/_default/config",
+];
+const CONFIG_EXTENSIONS: [&str; 4] = ["toml", "yaml", "yml", "json"];
+
+/// The parts of a Hugo site's configuration that decide where content goes
+#[derive(Debug, Default)]
+pub struct SiteConfig {
+    pub content_dir: Option<String>,
+    pub base_url: Option<String>,
+    /// The site's main section, from `params.mainSections` or, if there's only
+    /// one, the section with a permalink pattern
+    pub main_section: Option<String>,
+}
+
+impl SiteConfig {
+    pub fn read(root: &Path) -> SiteConfig {
+        let config = CONFIG_FILES
+            .iter()
+            .flat_map(|f| {
+                CONFIG_EXTENSIONS
+                    .iter()
+                    .map(move |ext| format!("{}.{}", f, ext))
+            })
+            .find_map(|f| parse(&root.join(f)));
+
+        let Some(config) = config else {
+            return SiteConfig::default();
+        };
+
+        let content_dir = get(&config, "contentDir")
+            .and_then(|d| d.as_str())
+            .map(String::from);
+
+        let base_url = get(&config, "baseURL")
+            .and_then(|u| u.as_str())
+            .map(String::from);
+
+        let main_sections = get(&config, "params")
+            .and_then(|p| get(p, "mainSections"))
+            .and_then(|s| s.as_array())
+            .and_then(|s| s.first())
+            .and_then(|s| s.as_str())
+            .map(String::from);
+        // Newer versions nest the sections under the kind of page
+        let permalinks = get(&config, "permalinks");
+        let permalink_section = permalinks
+            .and_then(|p| get(p, "page"))
+            .or(permalinks)
+            .and_then(|p| p.as_object())
+            .filter(|p| p.len() == 1)
+            .and_then(|p| p.keys().next().cloned());
+
+        SiteConfig {
+            content_dir,
+            base_url,
+            main_section: main_sections.or(permalink_section),
+        }
+    }
+}
+
+/// The archetype `hugo new` would use for a post in `section`, the one named
+/// after the top section or else `default.md`
+pub fn archetype(root: &Path, section: Option<&str>) -> Option<PathBuf> {
+    let archetypes = root.join(ARCHETYPES_DIRECTORY);
+    section
+        .and_then(|s| s.split('/').next())
+        .into_iter()
+        .chain(["default"])
+        .map(|name| archetypes.join(format!("{}.md", name)))
+        .find(|p| p.is_file())
+}
+
+/// The name of the variable a placeholder from an archetype, like `.Title`
+/// or `.Params.author`, stands for. The title Hugo's default archetype makes
+/// from the file name, `replace .File.ContentBaseName "-" " " | title`, is
+/// the title.
+pub fn variable(placeholder: &str) -> Option<&str> {
+    if let Some(("replace", arguments)) = placeholder.split_once(' ') {
+        let name = arguments.split_whitespace().next()?;
+        return (variable(name) == Some("slug") && placeholder.ends_with("| title"))
+            .then_some("title");
+    }
+    let name = placeholder.strip_prefix('.')?;
+    match name {
+        "Title" => Some("title"),
+        "Date" | "PublishDate" => Some("date"),
+        "Name" | "Slug" | "File.ContentBaseName" | "File.BaseFileName" => Some("slug"),
+        "Section" | "Type" => Some("section"),
+        "Lang" | "Language.Lang" => Some("lang"),
+        _ => name
+            .strip_prefix("Params.")
+            .or_else(|| name.strip_prefix("Site.Params.")),
+    }
+}
+
+/// Reads a config file into a generic value, whichever format it's in
+pub fn parse(path: &Path) -> Option<serde_json::Value> {
+    let contents = fs::read_to_string(path).ok()?;
+
+    match path.extension()?.to_str()? {
+        "toml" => toml::from_str(&contents).ok(),
+        "yaml" | "yml" => serde_yaml::from_str(&contents).ok(),
+        "json" => serde_json::from_str(&contents).ok(),
+        _ => None,
+    }
+}
+
+// Hugo's config keys are case-insensitive
+fn get<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a serde_json::Value> {
+    value
+        .as_object()?
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v)
+}
@@ -0,0 +1,103 @@
+use crate::config::PROJECT_CONFIG_FILES;
+use crate::error::Error;
+use crate::preset::Preset;
+use clap::{Args, ValueEnum};
+use std::fs;
+use std::path::Path;
+
+pub const TEMPLATES_DIRECTORY: &str = ".newpost/templates";
+
+#[derive(Args, Debug)]
+pub struct InitArguments {
+    /// Site generator to set the project up for, detected if not given
+    #[arg(long, value_enum)]
+    preset: Option<Preset>,
+
+    /// Also create a directory for post templates
+    #[arg(long)]
+    templates: bool,
+
+    /// Overwrite an existing config file
+    #[arg(long)]
+    force: bool,
+}
+
+/// Runs the `init` subcommand, setting up `root` for writing posts
+pub fn run(args: InitArguments, root: &Path) -> Result<(), Error> {
+    let config_path = root.join(PROJECT_CONFIG_FILES[0]);
+    if !args.force {
+        if let Some(existing) = PROJECT_CONFIG_FILES
+            .iter()
+            .map(|f| root.join(f))
+            .find(|f| f.is_file())
+        {
+            return Err(Error::from_string(
+                format!(
+                    "'{}' already exists, pass --force to overwrite it",
+                    existing.display()
+                )
+                .as_str(),
+            ));
+        }
+    }
+
+    let preset = args
+        .preset
+        .or_else(|| Preset::detect(root))
+        .unwrap_or(Preset::Zola);
+
+    let content_dir = preset
+        .site_content_directory(root)
+        .unwrap_or_else(|| root.join(preset.content_directory()));
+    let posts_dir = match preset.default_section(root) {
+        Some(section) => content_dir.join(section),
+        None => content_dir.clone(),
+    };
+    create_directory(&posts_dir)?;
+
+    if args.templates {
+        create_directory(&root.join(TEMPLATES_DIRECTORY))?;
+    }
+
+    let content_dir = content_dir
+        .strip_prefix(root)
+        .unwrap_or(&content_dir)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let config = format!(
+        r#"# Settings for new-post, `new-post config list` shows the values in effect
+preset = "{preset}"
+content_dir = "{content_dir}"
+# format = "{format}"
+# tags = []
+# editor = "vim"
+# filename = "{filename}"
+"#,
+        preset = name_of(preset),
+        content_dir = if content_dir.is_empty() {
+            "."
+        } else {
+            &content_dir
+        },
+        format = name_of(preset.default_format()),
+        filename = preset.file_name_pattern(),
+    );
+    fs::write(&config_path, config).map_err(|e| {
+        Error::from_error(&format!("Failed to write '{}'", config_path.display()), &e)
+    })?;
+
+    println!("Created {}", config_path.display());
+    Ok(())
+}
+
+fn create_directory(path: &Path) -> Result<(), Error> {
+    fs::create_dir_all(path)
+        .map_err(|e| Error::from_error(&format!("Failed to create '{}'", path.display()), &e))
+}
+
+fn name_of(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
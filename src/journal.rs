@@ -0,0 +1,91 @@
+use crate::date::{self, Zone};
+use crate::error::Error;
+use crate::project::Project;
+use crate::template;
+use crate::{create_post, PostOptions};
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::path::Path;
+
+/// Body of new weeknotes, unless there's a `weekly` template
+pub const WEEKLY_BODY: &str = "
+## What I did
+
+## What I learned
+
+## Next week
+";
+
+/// Runs the `weekly` subcommand, opening the weeknotes for the current week and
+/// creating them if they don't exist yet
+pub fn weekly(mut options: PostOptions, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, options.profile.clone())?;
+    let zone = Zone::from_setting(options.tz.as_deref().or(project.config.timezone.as_deref()))?;
+    let day = match &options.date {
+        Some(date) => date::parse_date(date, zone)?.date,
+        None => zone.now().date_naive(),
+    };
+
+    // Dated to the Monday so the file name is the same all week
+    let week = day.iso_week();
+    let monday = NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Mon)
+        .expect("the week of an existing day has a Monday");
+    options.date = Some(monday.to_string());
+    options
+        .slug
+        .get_or_insert_with(|| format!("{}-w{:02}", week.year(), week.week()));
+    open_existing_by_default(&mut options);
+
+    let body = if use_template(&mut options, &project.root, "weekly") {
+        ""
+    } else {
+        WEEKLY_BODY
+    };
+    let title = format!("Week {}, {}", week.week(), week.year());
+    create_post(&title, Vec::new(), body, None, None, options, current_dir)
+}
+
+/// Runs the `today` subcommand, opening today's daily note with a new entry
+/// headed by the time, and creating the note from the `daily.md` template if
+/// it doesn't exist yet
+pub fn today(mut options: PostOptions, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, options.profile.clone())?;
+    let zone = Zone::from_setting(options.tz.as_deref().or(project.config.timezone.as_deref()))?;
+    let now = zone.now();
+    let day = match &options.date {
+        Some(date) => date::parse_date(date, zone)?.date,
+        None => now.date_naive(),
+    };
+
+    options.date = Some(day.to_string());
+    options.slug.get_or_insert_with(|| day.to_string());
+    open_existing_by_default(&mut options);
+
+    let entry = format!("\n## {}\n\n", now.format("%H:%M"));
+    use_template(&mut options, &project.root, "daily");
+    let title = day.format("%A %-d %B %Y").to_string();
+    create_post(
+        &title,
+        Vec::new(),
+        &entry,
+        Some(&entry),
+        None,
+        options,
+        current_dir,
+    )
+}
+
+// Running the command again opens the post instead of failing
+fn open_existing_by_default(options: &mut PostOptions) {
+    if !options.force && options.on_collision.is_none() {
+        options.open_existing = true;
+    }
+}
+
+// Starts the post from the template named `name`, if there is one and no other
+// template was picked
+fn use_template(options: &mut PostOptions, root: &Path, name: &str) -> bool {
+    if options.template.is_none() && template::exists(root, name) {
+        options.template = Some(name.to_string());
+    }
+    options.template.is_some()
+}
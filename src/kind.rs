@@ -0,0 +1,88 @@
+use crate::error::Error;
+use crate::template::{self, Template};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::path::Path;
+
+/// A common sort of post, with its own front matter and body to start from
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// A link to something elsewhere, with a few words about it
+    Link,
+    /// Something learned today
+    Til,
+    /// A review of a book, film, product or the like
+    Review,
+    /// A short post, more or less without a title
+    Note,
+}
+
+impl Kind {
+    /// The name of the kind, and of the template that replaces the built-in one
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Link => "link",
+            Kind::Til => "til",
+            Kind::Review => "review",
+            Kind::Note => "note",
+        }
+    }
+
+    /// The site's template named after the kind, or the built-in one
+    pub fn template(self, root: &Path) -> Result<Template, Error> {
+        if template::exists(root, self.name()) {
+            return Template::find(root, self.name());
+        }
+
+        let contents = match self {
+            Kind::Link => LINK,
+            Kind::Til => TIL,
+            Kind::Review => REVIEW,
+            Kind::Note => NOTE,
+        };
+        Ok(Template::parse(contents).expect("the built-in templates have valid front matter"))
+    }
+}
+
+const LINK: &str = r#"+++
+[extra]
+kind = "link"
+link_url = ""
++++
+
+> A quote from the link
+
+What I think of it.
+"#;
+
+const TIL: &str = r#"+++
+tags = ["til"]
+
+[extra]
+kind = "til"
++++
+
+Today I learned
+"#;
+
+const REVIEW: &str = r#"+++
+[extra]
+kind = "review"
+rating = 0
++++
+
+## Summary
+
+## What worked
+
+## What didn't
+
+## Verdict
+"#;
+
+const NOTE: &str = r#"+++
+[extra]
+kind = "note"
++++
+"#;
@@ -0,0 +1,69 @@
+use crate::error::Error;
+use crate::front_matter::Extension;
+use serde::Deserialize;
+
+/// Where the translations of a multilingual site are kept
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageLayout {
+    /// A directory for each language, `content/fr/my-post.md`
+    Directory,
+    /// The language before the extension, `content/my-post.fr.md`, except for
+    /// the default language
+    Suffix,
+}
+
+/// The languages of a multilingual site, the `[languages]` table of the config
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LanguagesConfig {
+    pub layout: Option<LanguageLayout>,
+    /// Language codes posts may have, any when empty
+    pub codes: Vec<String>,
+    /// Language of the site, its posts don't get a suffix
+    pub default: Option<String>,
+    /// Front matter field with the post's language, `lang` by default
+    pub field: Option<String>,
+}
+
+impl LanguagesConfig {
+    /// Fails if the site doesn't have the language
+    pub fn check(&self, lang: &str) -> Result<(), Error> {
+        if self.codes.is_empty() || self.codes.iter().any(|c| c == lang) {
+            return Ok(());
+        }
+
+        Err(Error::from_string(
+            format!(
+                "The site has no language '{}', only {}",
+                lang,
+                self.codes.join(", ")
+            )
+            .as_str(),
+        ))
+    }
+
+    /// Directory in the content directory for posts in the language
+    pub fn directory<'a>(&self, lang: &'a str) -> Option<&'a str> {
+        match self.layout {
+            Some(LanguageLayout::Directory) => Some(lang),
+            Some(LanguageLayout::Suffix) | None => None,
+        }
+    }
+
+    /// The file name of a post in the language
+    pub fn file_name(&self, file_name: String, lang: &str, extension: Extension) -> String {
+        let is_default = self.default.as_deref() == Some(lang);
+        let ext = format!(".{}", extension.as_str());
+        match (self.layout, file_name.strip_suffix(&ext)) {
+            (Some(LanguageLayout::Suffix), Some(stem)) if !is_default => {
+                format!("{}.{}{}", stem, lang, ext)
+            }
+            _ => file_name,
+        }
+    }
+
+    pub fn field(&self) -> &str {
+        self.field.as_deref().unwrap_or("lang")
+    }
+}
@@ -0,0 +1,110 @@
+use crate::date::{self, Zone};
+use crate::error::Error;
+use crate::post;
+use crate::project::Project;
+use clap::Args;
+use std::path::Path;
+
+#[derive(Args, Debug)]
+pub struct ListArguments {
+    /// Only list posts dated in the future, the soonest first
+    #[arg(long)]
+    scheduled: bool,
+
+    /// Only list posts with the tag
+    #[arg(long)]
+    tag: Option<String>,
+
+    /// Only list posts in the section, e.g. `blog` or `blog/2024`
+    #[arg(long)]
+    section: Option<String>,
+
+    /// Only list drafts
+    #[arg(long)]
+    draft: bool,
+
+    /// Only list posts dated on or after the date, e.g. `2024-01-01` or `3 months ago`
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Only list posts dated on or before the date
+    #[arg(long)]
+    until: Option<String>,
+}
+
+/// Runs the `list` subcommand, printing the posts in the content directory that
+/// match the filters
+pub fn run(args: ListArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = &project.config;
+    let zone = Zone::from_setting(config.timezone.as_deref())?;
+    let now = zone.now();
+
+    let section = args.section.as_deref().unwrap_or_default();
+    let content_dir = project.content_dir(config, project.preset)?.join(section);
+    let drafts_dir = project
+        .drafts_dir(config, project.preset)
+        .map(|d| d.join(section));
+    let mut dirs = vec![content_dir];
+    dirs.extend(drafts_dir.clone());
+
+    let since = args
+        .since
+        .as_deref()
+        .map(|d| date::parse_date(d, zone))
+        .transpose()?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|d| date::parse_date(d, zone))
+        .transpose()?;
+
+    let mut posts = post::load_posts(&dirs)
+        .into_iter()
+        .filter_map(|post| Some((post.date(zone, config.date_format.as_deref())?, post)))
+        .collect::<Vec<_>>();
+    if args.scheduled {
+        posts.retain(|(date, _)| *date > now);
+    }
+    if let Some(tag) = &args.tag {
+        posts.retain(|(_, post)| post::field_strings(&post.front_matter, "tags").contains(tag));
+    }
+    if args.draft {
+        posts.retain(|(_, post)| {
+            post::field(&post.front_matter, "draft").and_then(|d| d.as_bool()) == Some(true)
+                || drafts_dir
+                    .as_ref()
+                    .is_some_and(|d| post.path.starts_with(d))
+        });
+    }
+    if let Some(since) = since {
+        posts.retain(|(date, _)| date.date_naive() >= since.date);
+    }
+    if let Some(until) = until {
+        posts.retain(|(date, _)| date.date_naive() <= until.date);
+    }
+    posts.sort_by_key(|(date, _)| *date);
+
+    for (date, post) in posts {
+        let path = post.path.strip_prefix(current_dir).unwrap_or(&post.path);
+        let mut line = format!("{}  ", date.format("%Y-%m-%d"));
+        if args.scheduled {
+            let days = (date.date_naive() - now.date_naive()).num_days();
+            line.push_str(&format!("{:<12} ", relative_day(days)));
+        }
+        println!("{}{}  {}", line, path.display(), post.title());
+    }
+
+    Ok(())
+}
+
+/// How far off a day `days` from today is, like `tomorrow` or `in 3 days`
+pub fn relative_day(days: i64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        -1 => "yesterday".to_string(),
+        2.. => format!("in {} days", days),
+        _ => format!("{} days ago", -days),
+    }
+}
@@ -1,164 +1,886 @@
-use chrono::{Local, NaiveDate};
-use clap::Parser;
+mod archive;
+mod astro;
+mod clone;
+mod collision;
+mod completions;
+mod config;
+mod date;
+mod delete;
+mod document;
+mod editor;
+mod error;
+mod front_matter;
+mod git;
+mod hugo;
+mod init;
+mod journal;
+mod kind;
+mod language;
+mod list;
+mod meta;
+mod newsletter;
+mod open;
+mod post;
+mod preset;
+mod project;
+mod prompt;
+mod publish;
+mod reading_time;
+mod release;
+mod relocate;
+mod rename;
+mod reschedule;
+mod section;
+mod slug;
+mod snippet;
+mod tags;
+mod template;
+mod touch;
+mod url;
+
+use crate::archive::ArchiveArguments;
+use crate::clone::CloneArguments;
+use crate::collision::{OnCollision, Target, BUNDLE_INDEX};
+use crate::completions::CompletionsArguments;
+use crate::config::ConfigAction;
+use crate::date::{PostDate, Zone};
+use crate::delete::DeleteArguments;
+use crate::editor::{OpenWith, Opener, Pane};
+use crate::error::Error;
+use crate::front_matter::{to_toml_date, to_toml_datetime, Extension, Format, FrontMatter, IdKind};
+use crate::init::InitArguments;
+use crate::kind::Kind;
+use crate::list::ListArguments;
+use crate::meta::MetaArguments;
+use crate::newsletter::IssueArguments;
+use crate::open::OpenArguments;
+use crate::preset::{Preset, DEFAULT_FILE_NAME_PATTERN};
+use crate::project::Project;
+use crate::publish::{PublishArguments, UnpublishArguments};
+use crate::reading_time::ReadingTimeArguments;
+use crate::release::ReleaseArguments;
+use crate::relocate::MoveArguments;
+use crate::rename::RenameArguments;
+use crate::reschedule::RescheduleArguments;
+use crate::section::SectionArguments;
+use crate::slug::create_safe_file_name;
+use crate::snippet::SnippetArguments;
+use crate::tags::TagsArguments;
+use crate::template::Template;
+use crate::touch::TouchArguments;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+use std::collections::BTreeMap;
 use std::env::current_dir;
-use std::ffi::OsStr;
-use std::fmt::{Display, Formatter};
+use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::{env, fs};
+use toml::Table;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Arguments {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    #[command(flatten)]
+    post: PostArguments,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Move the posts older than a date to the `archive` section
+    Archive(ArchiveArguments),
+    /// Create a post from the front matter and headings of an existing one
+    Clone(Box<CloneArguments>),
+    /// Print a completion script for the shell
+    Completions(CompletionsArguments),
+    /// Inspect or change the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+
+        /// Use the global config instead of the project config
+        #[arg(long, global = true)]
+        global: bool,
+    },
+    /// Move a post to the trash, or delete it for good with `--permanent`
+    Delete(DeleteArguments),
+    /// Set up the current directory for writing posts
+    Init(InitArguments),
+    /// Create the next issue of a newsletter, numbered after the last one
+    Issue(Box<IssueArguments>),
+    /// List the posts, oldest first
+    List(ListArguments),
+    /// Change the front matter of a post, e.g. `meta <post> add tags rust`
+    Meta(MetaArguments),
+    /// Move a post to another section, keeping its old URL as an alias
+    Move(MoveArguments),
+    /// Open an existing post, found by its slug or title
+    Open(OpenArguments),
+    /// Mark a draft as published today, moving it out of the drafts directory
+    Publish(PublishArguments),
+    /// Write how many minutes it takes to read a post to its front matter
+    ReadingTime(ReadingTimeArguments),
+    /// Create release notes for a version, with a changelog to fill in
+    Release(Box<ReleaseArguments>),
+    /// Change the title and file name of a post, keeping its old URL as an alias
+    Rename(RenameArguments),
+    /// Move a post to another date, taking the same dates as `--date`
+    Reschedule(RescheduleArguments),
+    /// Create a section with an `_index.md`
+    Section(SectionArguments),
+    /// Add a snippet from the config, like a figure or an embed, to a post
+    Snippet(SnippetArguments),
+    /// List the tags on the site, with how many posts have each
+    Tags(TagsArguments),
+    /// Set the updated date of a post to now
+    Touch(TouchArguments),
+    /// Mark a post as a draft again, keeping its date in `first_published`
+    Unpublish(UnpublishArguments),
+    /// Open this week's weeknotes, named after the ISO week, creating them if needed
+    Weekly(Box<PostOptions>),
+    /// Open today's daily note, adding a new entry to it if it already exists
+    Today(Box<PostOptions>),
+}
+
+#[derive(Args, Debug)]
+struct PostArguments {
     /// Title of the post (also used to derive file name)
-    title: String,
+    #[arg(required = true)]
+    title: Option<String>,
 
-    /// Tags to add ot the front matter
+    /// Tags to add ot the front matter (deprecated, use `--tag`)
     tags: Vec<String>,
 
+    #[command(flatten)]
+    options: PostOptions,
+}
+
+/// Options for creating posts, shared by the subcommands that do
+#[derive(Args, Debug)]
+struct PostOptions {
+    /// Slug for the file name and the `slug` front matter field, instead of
+    /// deriving it from the title
+    #[arg(long)]
+    slug: Option<String>,
+
+    /// Language of the post, e.g. `de` or `nb`, for language specific slug rules
+    /// and, on multilingual sites, where the post goes
+    #[arg(long)]
+    lang: Option<String>,
+
+    /// Write the slug to the front matter even when it's derived from the title
+    #[arg(long)]
+    slug_field: bool,
+
     /// Command to run to open the newly created file
     #[arg(short, long)]
     editor: Option<String>,
-}
 
-fn main() -> Result<(), Error> {
-    let args = Arguments::parse();
+    /// Open the post in the editor, or in the system's default application
+    #[arg(long, value_enum)]
+    open: Option<OpenWith>,
 
-    let today = Local::now().date_naive();
+    /// Start the editor without waiting for it to close, for editors with their
+    /// own window
+    #[arg(long)]
+    detach: bool,
 
-    let content_dir = locate_content_directory()?;
+    /// Open the editor in a new tmux, WezTerm or kitty pane, so the terminal
+    /// stays free. The multiplexer is detected when it's left out.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "auto")]
+    pane: Option<Pane>,
 
-    let new_file_path = content_dir.join(format!("{}.md", create_safe_file_name(&args.title)));
+    /// Keep the post when the editor is closed without changing it, instead of
+    /// offering to delete it
+    #[arg(long)]
+    keep_empty: bool,
 
-    write_file_contents(&args.title, today, args.tags, new_file_path.as_path())?;
+    /// Don't open the post, only print its path
+    #[arg(long, conflicts_with = "editor")]
+    no_edit: bool,
 
-    let editor = get_editor_command_string(args.editor)?;
+    /// Format of the front matter block
+    #[arg(long, value_enum)]
+    format: Option<Format>,
 
-    run_editor(editor, new_file_path.as_path())?;
+    /// Markup language of the new file
+    #[arg(long, value_enum)]
+    ext: Option<Extension>,
 
-    Ok(())
+    /// Follow the conventions of a specific static site generator
+    #[arg(long, value_enum)]
+    preset: Option<Preset>,
+
+    /// Template to start the post from, e.g. `review` for
+    /// `.newpost/templates/review.md` or `templates/review.md`
+    #[arg(short, long)]
+    template: Option<String>,
+
+    /// Kind of post to start from, unless a template is given. The site's
+    /// template by the same name is used instead of the built-in one.
+    #[arg(long, value_enum, conflicts_with = "template")]
+    kind: Option<Kind>,
+
+    /// Short description of the post, for search engines and link previews
+    #[arg(long, visible_alias = "summary")]
+    description: Option<String>,
+
+    /// Tag to add to the front matter, can be repeated
+    #[arg(short = 'T', long = "tag", value_name = "TAG")]
+    tag: Vec<String>,
+
+    /// Allow tags that aren't in the config's `tag_rules.allowed`
+    #[arg(long)]
+    allow_new_tags: bool,
+
+    /// Category to add the post to, can be repeated
+    #[arg(short, long = "category", value_name = "CATEGORY")]
+    categories: Vec<String>,
+
+    /// Extra front matter field, e.g. `--meta toc=true`, can be repeated
+    #[arg(long = "meta", value_name = "KEY=VALUE", value_parser = front_matter::parse_field)]
+    meta: Vec<(String, toml::Value)>,
+
+    /// Series the post is a part of, the part number follows the posts
+    /// already in the series
+    #[arg(long)]
+    series: Option<String>,
+
+    /// Weight to order the post by in its section, `auto` for one more than
+    /// the heaviest post in the section
+    #[arg(long, value_name = "WEIGHT|auto", value_parser = parse_weight)]
+    weight: Option<Weight>,
+
+    /// Old URL to redirect to the post, can be repeated
+    #[arg(long = "alias", value_name = "URL")]
+    aliases: Vec<String>,
+
+    /// Give the post a stable `id` in the extra fields, for comments, analytics
+    /// and the like
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "uuid")]
+    id: Option<IdKind>,
+
+    /// Author of the post, defaults to the name in the git config
+    #[arg(long = "author", value_name = "AUTHOR")]
+    authors: Vec<String>,
+
+    /// Layout (template) the post is rendered with
+    #[arg(long)]
+    layout: Option<String>,
+
+    /// Date of the post instead of today, e.g. `2024-06-01 14:00`, `yesterday`
+    /// or `next monday`
+    #[arg(long)]
+    date: Option<String>,
+
+    /// Schedule the post to be published after a while, e.g. `3d`, `2w` or `1d12h`
+    #[arg(long, value_name = "DURATION", conflicts_with_all = ["date", "publish_at"])]
+    publish_in: Option<String>,
+
+    /// Schedule the post to be published at a later date, e.g. `friday 09:00`
+    #[arg(long, value_name = "DATE", conflicts_with = "date")]
+    publish_at: Option<String>,
+
+    /// Timezone of the date, e.g. `Europe/Oslo`, instead of the machine's
+    #[arg(long)]
+    tz: Option<String>,
+
+    /// Include the time of day in the date, not just the day
+    #[arg(long)]
+    now: bool,
+
+    /// Only write the day in the date, even when it's given with a time
+    #[arg(long, conflicts_with = "now")]
+    date_only: bool,
+
+    /// Add an updated date, the same as the post date
+    #[arg(long)]
+    updated: bool,
+
+    /// Permalink to publish the post at
+    #[arg(long)]
+    permalink: Option<String>,
+
+    /// Create the post as `<slug>/index.md`, so images and other files can be
+    /// kept next to it
+    #[arg(long)]
+    bundle: bool,
+
+    /// Create the content directory if there isn't one, instead of asking
+    #[arg(long)]
+    create_dirs: bool,
+
+    /// Give the section directories that are created for the post an `_index.md`
+    #[arg(long)]
+    section_index: bool,
+
+    /// Site to create the post in, one of the `sites` in the config of a monorepo
+    #[arg(long, conflicts_with = "dir")]
+    site: Option<String>,
+
+    /// Directory to create the post in, instead of looking for the content
+    /// directory
+    #[arg(long, conflicts_with = "section")]
+    dir: Option<PathBuf>,
+
+    /// Section (directory in the content directory) to add the post to, may be
+    /// nested like `rust/async`. For Astro this is the content collection
+    #[arg(long, visible_alias = "collection")]
+    section: Option<String>,
+
+    /// Mark the post as a draft, and create it in the drafts directory if the
+    /// site has one
+    #[arg(short, long)]
+    draft: bool,
+
+    /// What to do if a file with the same name already exists
+    #[arg(long, value_enum)]
+    on_collision: Option<OnCollision>,
+
+    /// Overwrite an existing file with the same name
+    #[arg(short, long, conflicts_with = "open_existing")]
+    force: bool,
+
+    /// Open the existing file instead if one with the same name already exists
+    #[arg(long)]
+    open_existing: bool,
+
+    /// Profile from the config to use defaults from [env: NEW_POST_PROFILE]
+    #[arg(long)]
+    profile: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Weight {
+    Auto,
+    Value(i64),
 }
 
-fn locate_content_directory() -> Result<PathBuf, Error> {
+fn parse_weight(weight: &str) -> Result<Weight, String> {
+    match weight {
+        "auto" => Ok(Weight::Auto),
+        _ => weight
+            .parse()
+            .map(Weight::Value)
+            .map_err(|_| format!("expected a number or `auto`, got '{}'", weight)),
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let args = Arguments::parse();
+
     let current_dir = current_dir()
         .map_err(|e| Error::from_error("Failed to get current working directory", &e))?;
 
-    let content_directory_name = OsStr::new("content");
-    if current_dir.file_name() == Some(content_directory_name) {
-        return Ok(current_dir);
+    match args.command {
+        Some(Commands::Archive(archive_args)) => archive::run(archive_args, &current_dir),
+        Some(Commands::Clone(clone_args)) => clone::run(*clone_args, &current_dir),
+        Some(Commands::Completions(completions_args)) => {
+            completions::run(completions_args, &current_dir)
+        }
+        Some(Commands::Config { action, global }) => config::run(action, global, &current_dir),
+        Some(Commands::Delete(delete_args)) => delete::run(delete_args, &current_dir),
+        Some(Commands::Init(init_args)) => init::run(init_args, &current_dir),
+        Some(Commands::Issue(issue_args)) => newsletter::run(*issue_args, &current_dir),
+        Some(Commands::List(list_args)) => list::run(list_args, &current_dir),
+        Some(Commands::Meta(meta_args)) => meta::run(meta_args, &current_dir),
+        Some(Commands::Move(move_args)) => relocate::run(move_args, &current_dir),
+        Some(Commands::Open(open_args)) => open::run(open_args, &current_dir),
+        Some(Commands::Publish(publish_args)) => publish::run(publish_args, &current_dir),
+        Some(Commands::ReadingTime(reading_time_args)) => {
+            reading_time::run(reading_time_args, &current_dir)
+        }
+        Some(Commands::Release(release_args)) => release::run(*release_args, &current_dir),
+        Some(Commands::Rename(rename_args)) => rename::run(rename_args, &current_dir),
+        Some(Commands::Reschedule(reschedule_args)) => {
+            reschedule::run(reschedule_args, &current_dir)
+        }
+        Some(Commands::Section(section_args)) => section::run(section_args, &current_dir),
+        Some(Commands::Snippet(snippet_args)) => snippet::run(snippet_args, &current_dir),
+        Some(Commands::Tags(tags_args)) => tags::run(tags_args, &current_dir),
+        Some(Commands::Touch(touch_args)) => touch::run(touch_args, &current_dir),
+        Some(Commands::Unpublish(unpublish_args)) => {
+            publish::unpublish(unpublish_args, &current_dir)
+        }
+        Some(Commands::Weekly(options)) => journal::weekly(*options, &current_dir),
+        Some(Commands::Today(options)) => journal::today(*options, &current_dir),
+        None => {
+            let title = args
+                .post
+                .title
+                .expect("title is required without a subcommand");
+            if !args.post.tags.is_empty() {
+                eprintln!("Tags after the title are deprecated, give them with --tag instead");
+            }
+            let mut tags = args.post.tags;
+            if tags.is_empty() && args.post.options.tag.is_empty() {
+                tags = tags::pick(&current_dir, args.post.options.profile.clone(), &title)?;
+            }
+            create_post(
+                &title,
+                tags,
+                "",
+                None,
+                None,
+                args.post.options,
+                &current_dir,
+            )
+        }
     }
+}
 
-    current_dir
-        .read_dir()
-        .map_err(|e| Error::from_error("Failed to get children of current working directory", &e))?
-        .filter_map(|c| {
-            if let Ok(de) = c {
-                if de.file_type().ok()?.is_dir() {
-                    Some(de)
+/// The number of the next part of a series, after the highest numbered part in
+/// the directories, or the number of posts in the series if they aren't numbered
+fn next_part<'a>(series: &str, dirs: impl IntoIterator<Item = Option<&'a PathBuf>>) -> i64 {
+    let parts = dirs
+        .into_iter()
+        .flatten()
+        .flat_map(|dir| post::find_posts(dir))
+        .filter_map(|path| post::read_front_matter(&path).ok())
+        .filter(|table| {
+            post::field_strings(table, "series")
+                .iter()
+                .any(|s| s == series)
+        })
+        .map(|table| post::field(&table, "part").and_then(toml::Value::as_integer))
+        .collect::<Vec<_>>();
+
+    parts
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(parts.len() as i64)
+        + 1
+}
+
+/// Creates a post and opens it in the editor, `body` is written after the front
+/// matter and the template's body. When the post is opened instead because it
+/// exists, `append` is added to the end of it first. `template` is used instead
+/// of the one the options and config pick.
+fn create_post(
+    title: &str,
+    extra_tags: Vec<String>,
+    body: &str,
+    append: Option<&str>,
+    template: Option<Template>,
+    args: PostOptions,
+    current_dir: &Path,
+) -> Result<(), Error> {
+    // The section decides which section settings apply, so it's resolved
+    // before the rest of the settings
+    let project = Project::load(current_dir, args.profile)?;
+    let project = match &args.site {
+        Some(site) => project.site(site)?,
+        None => project,
+    };
+    let root = project.root.clone();
+    let preset = args.preset.or(project.preset);
+    let section = args
+        .section
+        .or(project.config.section.clone())
+        .or_else(|| preset.and_then(|p| p.default_section(&root)));
+    let config = project.section_config(section.as_deref())?;
+
+    let keep_empty = args.keep_empty || config.keep_empty;
+    // Found before the file is written so it isn't left behind without one
+    let opener = if args.no_edit {
+        Opener::Print
+    } else {
+        match args.open.or(config.open).unwrap_or_default() {
+            OpenWith::Editor => {
+                let command = editor::command_string(args.editor.or(config.editor.clone()))?;
+                // The system's opener returns before the post is even open
+                if editor::is_system_opener(&command) {
+                    Opener::System
                 } else {
+                    Opener::Editor {
+                        command,
+                        detach: args.detach || config.detach,
+                        pane: args.pane.or(config.pane).map(Pane::resolve).transpose()?,
+                    }
+                }
+            }
+            OpenWith::System => Opener::System,
+        }
+    };
+
+    // Posts from an archetype get its front matter format, unless one is set
+    let mut archetype_format = None;
+    let mut template = match (&args.template, args.kind, &config.template, config.kind) {
+        _ if template.is_some() => template,
+        (Some(name), _, _, _) => Some(Template::find(&root, name)?),
+        (None, Some(kind), _, _) => Some(kind.template(&root)?),
+        (None, None, Some(name), _) => Some(Template::find(&root, name)?),
+        (None, None, None, Some(kind)) => Some(kind.template(&root)?),
+        (None, None, None, None) if preset == Some(Preset::Hugo) => {
+            // A broken archetype shouldn't stop posts from being created
+            let archetype = hugo::archetype(&root, section.as_deref());
+            archetype_format = archetype
+                .as_ref()
+                .and_then(|p| fs::read_to_string(p).ok())
+                .and_then(|c| Format::detect(&c));
+            match archetype.map(|p| Template::read(&p)) {
+                Some(Ok(template)) => Some(template),
+                Some(Err(e)) => {
+                    eprintln!("{}, using the built-in front matter instead", e);
                     None
                 }
-            } else {
-                None
+                None => None,
             }
-        })
-        .find(|dir| dir.file_name() == content_directory_name)
-        .ok_or(Error::from_string(
+        }
+        (None, None, None, None) => None,
+    };
+
+    let zone = Zone::from_setting(args.tz.as_deref().or(config.timezone.as_deref()))?;
+    let now = zone.now();
+    let post_date = match (&args.date, &args.publish_in, &args.publish_at) {
+        (Some(date), _, _) => date::parse_date(date, zone)?,
+        (_, Some(duration), _) => date::after(now, duration)?,
+        (_, _, Some(date)) => {
+            let date = date::parse_date(date, zone)?;
+            let publish_at = zone.at(date.date, date.time.unwrap_or_default());
+            if date
+                .time
+                .map_or(date.date <= now.date_naive(), |_| publish_at <= now)
+            {
+                return Err(Error::from_string(
+                    "The publish date has to be in the future, use --date for past dates",
+                ));
+            }
+            date
+        }
+        (None, None, None) => PostDate {
+            date: now.date_naive(),
+            time: None,
+        },
+    };
+
+    let format = args
+        .format
+        .or(config.format)
+        .or(archetype_format)
+        .or_else(|| preset.map(|p| p.default_format()))
+        .unwrap_or_default();
+
+    // A directory given with `--dir` is used as it is
+    let published_dir = match &args.dir {
+        Some(dir) => current_dir.join(dir),
+        None => project.content_dir_or_create(&config, preset, args.create_dirs)?,
+    };
+    let mut content_dir = published_dir.clone();
+    let drafts_dir = project.drafts_dir(&config, preset);
+    let draft = args.draft || config.draft;
+    let in_drafts_dir = draft && drafts_dir.is_some() && args.dir.is_none();
+    if let (true, Some(drafts_dir)) = (in_drafts_dir, &drafts_dir) {
+        content_dir = drafts_dir.clone();
+    }
+    // Numbered after the posts already in the series
+    let series = args.series.clone().map(|series| {
+        let part = next_part(&series, [Some(&published_dir), drafts_dir.as_ref()]);
+        (series, part)
+    });
+    let title = match (&series, &config.series_title) {
+        (Some((series, part)), Some(pattern)) => pattern
+            .replace("{title}", title)
+            .replace("{series}", series)
+            .replace("{part}", &part.to_string()),
+        _ => title.to_string(),
+    };
+    let title = title.as_str();
+
+    // Translations go where the site's language layout has them
+    let lang = args.lang.clone().or(config.lang.clone());
+    let languages = &config.languages;
+    let site_lang = lang.as_deref().filter(|_| languages.layout.is_some());
+    if let Some(lang) = site_lang {
+        languages.check(lang)?;
+    }
+    if let (Some(dir), None) = (site_lang.and_then(|l| languages.directory(l)), &args.dir) {
+        content_dir = content_dir.join(dir);
+    }
+    let mut new_sections = Vec::new();
+    if let (Some(section), None) = (&section, &args.dir) {
+        new_sections = section::missing_dirs(&content_dir, section);
+        content_dir = content_dir.join(section);
+    }
+    fs::create_dir_all(&content_dir)
+        .map_err(|e| Error::from_error("Failed to create the post's directory", &e))?;
+
+    let extension = args.ext.or(config.ext).unwrap_or_default();
+    if args.section_index || config.section_index {
+        for dir in &new_sections {
+            let front_matter = section::index_front_matter(dir, &config.index);
+            section::write_index(dir, &front_matter, format, extension)?;
+        }
+    }
+    // Drafts get their date when they're published
+    let file_name_pattern = match (config.filename.as_deref(), preset) {
+        (Some(pattern), _) => pattern,
+        (None, Some(preset)) if !in_drafts_dir => preset.file_name_pattern(),
+        (None, _) => DEFAULT_FILE_NAME_PATTERN,
+    };
+    let write_slug = args.slug.is_some() || args.slug_field || config.slug.field;
+    let slug = args
+        .slug
+        .unwrap_or_else(|| create_safe_file_name(title, lang.as_deref(), &config.slug));
+    if slug.is_empty() {
+        return Err(Error::from_string(
             format!(
-                "Failed to find a directory named '{}'",
-                content_directory_name.to_string_lossy()
+                "The slug made from the title '{}' is empty, give one with --slug",
+                title
             )
             .as_str(),
-        ))
-        .map(|de| de.path())
-}
+        ));
+    }
+    let mut file_name = expand_file_name(file_name_pattern, &slug, post_date.date, extension);
+    // A bundle is a directory named like the file would be, with the post as
+    // its index so assets can go next to it
+    if args.bundle || config.bundle {
+        let dir_name = file_name
+            .strip_suffix(&format!(".{}", extension.as_str()))
+            .unwrap_or(&file_name);
+        file_name = format!("{}/{}.{}", dir_name, BUNDLE_INDEX, extension.as_str());
+    }
+    if let Some(lang) = site_lang {
+        file_name = languages.file_name(file_name, lang, extension);
+    }
+    let extra_tags = args
+        .tag
+        .iter()
+        .chain(&extra_tags)
+        .map(|t| config.tag_rules.normalize(t))
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>();
+    if !args.allow_new_tags {
+        config.tag_rules.check(&extra_tags, &root)?;
+    }
+    let on_collision = if args.force {
+        OnCollision::Overwrite
+    } else if args.open_existing {
+        OnCollision::Open
+    } else {
+        args.on_collision
+            .or(config.on_collision)
+            .unwrap_or_default()
+    };
+    let new_file_path = match collision::resolve(content_dir.join(file_name), on_collision)? {
+        Target::New(path) => path,
+        Target::Existing(path) => {
+            if let Some(text) = append {
+                append_to_file(&path, text)?;
+            }
+            return opener.open(&path);
+        }
+    };
 
-fn write_file_contents(
-    title: &str,
-    date: NaiveDate,
-    tags: Vec<String>,
-    file_path: &Path,
-) -> Result<(), Error> {
-    let file_contents = format!(
-        r#"+++
-title = "{title}"
-date = {date}
-[taxonomies]
-tags = [{tags}]
-+++
-"#,
-        title = title,
-        date = date.format("%Y-%m-%d"),
-        tags = tags
-            .iter()
-            .map(|s| format!(r#""{}""#, s))
-            .collect::<Vec<_>>()
-            .join(", "),
-    );
+    let timestamps = if args.now {
+        true
+    } else {
+        config
+            .timestamps
+            .and_then(|t| t.for_format(format))
+            .unwrap_or_else(|| preset.is_some_and(|p| p.timestamps()))
+    };
+    let date = match post_date.time {
+        _ if args.date_only => to_toml_date(post_date.date),
+        Some(time) => to_toml_datetime(zone.at(post_date.date, time)),
+        None if timestamps => to_toml_datetime(zone.at(post_date.date, now.time())),
+        None => to_toml_date(post_date.date),
+    };
+    let mut tags = config.tags;
+    for tag in extra_tags {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    let mut categories = config.categories;
+    for category in args.categories {
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+    let mut front_matter = FrontMatter::new(title, date, tags);
+    front_matter.categories = categories;
+    front_matter.taxonomies = config.taxonomies;
+    front_matter.date_format = config.date_format;
+    if write_slug {
+        front_matter.slug = Some(slug.clone());
+    }
+    front_matter.layout = args.layout.or(config.layout);
+    front_matter.permalink = args.permalink.or(config.permalink);
+    front_matter.description = args.description;
+    front_matter.weight = args.weight.map(|weight| match weight {
+        Weight::Value(weight) => weight,
+        Weight::Auto => {
+            post::find_posts(&content_dir)
+                .iter()
+                .filter_map(|path| post::read_front_matter(path).ok())
+                .filter_map(|table| post::field(&table, "weight")?.as_integer())
+                .max()
+                .unwrap_or(0)
+                + 1
+        }
+    });
+    front_matter.aliases = args.aliases;
+    if let Some(id) = args.id.or(config.id) {
+        front_matter
+            .extra
+            .insert("id".to_string(), toml::Value::String(id.generate()));
+    }
+    if let Some(lang) = site_lang {
+        front_matter.extra.insert(
+            languages.field().to_string(),
+            toml::Value::String(lang.to_string()),
+        );
+    }
+    front_matter.extra.extend(args.meta);
+    if let Some((series, part)) = series {
+        front_matter
+            .extra
+            .insert("series".to_string(), toml::Value::String(series));
+        front_matter
+            .extra
+            .insert("part".to_string(), toml::Value::Integer(part));
+    }
+    if draft {
+        front_matter.draft = Some(true);
+    }
+    if args.updated || config.updated {
+        front_matter.updated = Some(date);
+    }
+    front_matter.authors = if !args.authors.is_empty() {
+        args.authors
+    } else {
+        config
+            .authors
+            .unwrap_or_else(|| git::user_name(&root).into_iter().collect())
+    };
+    if let Some(template) = &mut template {
+        let mut variables = [
+            ("title", title.to_string()),
+            ("date", post_date.date.to_string()),
+            ("slug", slug),
+            ("tags", front_matter.tags.join(", ")),
+            (
+                "author",
+                front_matter.authors.first().cloned().unwrap_or_default(),
+            ),
+            ("authors", front_matter.authors.join(", ")),
+            ("section", section.clone().unwrap_or_default()),
+            ("lang", lang.clone().unwrap_or_default()),
+        ]
+        .map(|(k, v)| (k.to_string(), v))
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+        let git_values = [
+            ("git.branch", git::branch(&root)),
+            ("git.user.name", git::user_name(&root)),
+            ("git.remote", git::remote_url(&root)),
+        ];
+        for (key, value) in git_values {
+            variables.insert(key.to_string(), value.unwrap_or_default());
+        }
+        // The config's variables can fill in for the ones that aren't known,
+        // like the author outside of a repository
+        variables.extend(
+            config
+                .variables
+                .iter()
+                .map(|(k, v)| (k.clone(), template::value_text(v))),
+        );
+        template.render(&variables);
+        template.apply(&mut front_matter);
+    }
+    if let Some(preset) = preset {
+        preset.prepare(&mut front_matter);
+    }
 
-    fs::write(file_path, file_contents)
-        .map(|_| ())
-        .map_err(|e| Error::from_error("Failed to create file", &e))
-}
+    let mut table = front_matter.to_table(format, preset)?;
+    if let (Some(Preset::Astro), Some(collection)) = (preset, &section) {
+        let fields = astro::collection_fields(&root, collection);
+        astro::fill_required_fields(&mut table, &fields, date);
+    }
+    if let Some(fields) = &config.fields {
+        front_matter::select_fields(&mut table, fields);
+    }
 
-fn create_safe_file_name(title: &str) -> String {
-    title
-        .replace(&['\'', '"', '(', ')'], "")
-        .replace(' ', "-")
-        .to_lowercase()
-}
+    // The template's body comes before the one the post was created with
+    let body = match &template {
+        Some(template) => template.body.clone() + body,
+        None => body.to_string(),
+    };
+    write_file_contents(&table, format, extension, &body, new_file_path.as_path())?;
 
-fn get_editor_command_string(editor_path: Option<String>) -> Result<String, Error> {
-    if let Some(cmd) = editor_path {
-        Ok(cmd)
-    } else {
-        env::var("VISUAL")
-            .or_else(|_| env::var("EDITOR"))
-            .map_err(|_| Error::from_string("Unable to find a valid path to an editor"))
+    // Like `git commit`, a post left as it was is taken as abandoned
+    let written = fs::read_to_string(&new_file_path).unwrap_or_default();
+    let check_changes = opener.waits() && !keep_empty;
+    opener.open(new_file_path.as_path())?;
+    if check_changes {
+        remove_if_unchanged(&new_file_path, &written)?;
     }
-}
 
-fn run_editor(editor: String, file_path: &Path) -> Result<(), Error> {
-    let mut editor_args = editor.split(' ').collect::<Vec<_>>();
-    editor_args.push(
-        file_path
-            .as_os_str()
-            .to_str()
-            .expect("path with no trixie characters"),
-    );
-
-    let mut command = Command::new(editor_args[0]);
-    command.args(editor_args.iter().skip(1));
-
-    command
-        .spawn()
-        .map_err(|e| Error::from_error("Failed to start editor process", &e))?
-        .wait()
-        .map_err(|e| Error::from_error("Error occured during editor run time", &e))
-        .map(|_| ())
+    Ok(())
 }
 
-#[derive(Debug)]
-struct Error {
-    message: String,
+// Offers to delete a new post the editor was closed on without changes
+fn remove_if_unchanged(file_path: &Path, written: &str) -> Result<(), Error> {
+    let unchanged = fs::read_to_string(file_path).is_ok_and(|contents| contents == written);
+    let question = format!("'{}' wasn't changed. Delete it?", file_path.display());
+    if unchanged && prompt::confirm(&question, true)? {
+        fs::remove_file(file_path)
+            .map_err(|e| Error::from_error("Failed to delete the post", &e))?;
+        eprintln!("Deleted '{}'", file_path.display());
+    }
+
+    Ok(())
 }
 
-impl Error {
-    fn from_error(message: &str, error: &dyn Display) -> Self {
-        Error {
-            message: format!("{}: {}", message, error),
-        }
-    }
+fn write_file_contents(
+    table: &Table,
+    format: Format,
+    extension: Extension,
+    body: &str,
+    file_path: &Path,
+) -> Result<(), Error> {
+    let file_contents = extension.render(table, format)? + body;
 
-    fn from_string(message: &str) -> Self {
-        Error {
-            message: message.to_string(),
-        }
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| Error::from_error("Failed to create directory for file", &e))?;
     }
+
+    fs::write(file_path, file_contents)
+        .map(|_| ())
+        .map_err(|e| Error::from_error("Failed to create file", &e))
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.message)
-    }
+fn append_to_file(file_path: &Path, text: &str) -> Result<(), Error> {
+    let mut contents = fs::read_to_string(file_path)
+        .map_err(|e| Error::from_error("Failed to read existing file", &e))?;
+    contents.truncate(contents.trim_end().len());
+    contents.push('\n');
+    contents.push_str(text);
+
+    fs::write(file_path, contents).map_err(|e| Error::from_error("Failed to update file", &e))
+}
+
+/// Expands the `{slug}`, `{date}`, `{year}`, `{month}`, `{day}` and `{ext}`
+/// placeholders in a file name pattern. The pattern may contain directories.
+pub fn expand_file_name(
+    pattern: &str,
+    slug: &str,
+    date: NaiveDate,
+    extension: Extension,
+) -> String {
+    pattern
+        .replace("{slug}", slug)
+        .replace("{date}", &date.format("%Y-%m-%d").to_string())
+        .replace("{year}", &date.format("%Y").to_string())
+        .replace("{month}", &date.format("%m").to_string())
+        .replace("{day}", &date.format("%d").to_string())
+        .replace("{ext}", extension.as_str())
 }
@@ -0,0 +1,87 @@
+use crate::document::Document;
+use crate::error::Error;
+use crate::front_matter;
+use crate::post;
+use clap::{Args, Subcommand};
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+#[derive(Args, Debug)]
+pub struct MetaArguments {
+    /// Post to change the front matter of
+    post: PathBuf,
+
+    #[command(subcommand)]
+    action: MetaAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetaAction {
+    /// Print the value of a field
+    Get { key: String },
+    /// Change fields, e.g. `draft=false`, the values are read as TOML if
+    /// possible and as strings otherwise
+    Set {
+        #[arg(required = true, value_name = "KEY=VALUE", value_parser = front_matter::parse_field)]
+        fields: Vec<(String, Value)>,
+    },
+    /// Add values to a list field like `tags`, if they aren't in it already
+    Add {
+        key: String,
+        #[arg(required = true)]
+        values: Vec<String>,
+    },
+    /// Remove values from a list field like `tags`
+    Remove {
+        key: String,
+        #[arg(required = true)]
+        values: Vec<String>,
+    },
+}
+
+/// Runs the `meta` subcommand, changing the front matter of a post while
+/// leaving the rest of the file as it is
+pub fn run(args: MetaArguments, current_dir: &Path) -> Result<(), Error> {
+    let mut document = Document::read(&current_dir.join(&args.post))?;
+    let table = document.table();
+
+    match args.action {
+        MetaAction::Get { key } => {
+            match post::field(&table, &key) {
+                Some(Value::String(value)) => println!("{}", value),
+                Some(value) => println!("{}", value),
+                None => {
+                    return Err(Error::from_string(
+                        format!("The post has no '{}' field", key).as_str(),
+                    ))
+                }
+            }
+            return Ok(());
+        }
+        MetaAction::Set { fields } => {
+            for (key, value) in fields {
+                document.set(&key, value, "");
+            }
+        }
+        MetaAction::Add { key, values } => {
+            let mut list = post::field_strings(&table, &key);
+            for value in values {
+                if !list.contains(&value) {
+                    list.push(value);
+                }
+            }
+            document.set(&key, to_array(list), "");
+        }
+        MetaAction::Remove { key, values } => {
+            let mut list = post::field_strings(&table, &key);
+            list.retain(|v| !values.contains(v));
+            document.set(&key, to_array(list), "");
+        }
+    }
+
+    document.write()
+}
+
+fn to_array(values: Vec<String>) -> Value {
+    Value::Array(values.into_iter().map(Value::String).collect())
+}
@@ -0,0 +1,62 @@
+use crate::error::Error;
+use crate::post;
+use crate::project::Project;
+use crate::{create_post, PostOptions};
+use clap::Args;
+use std::path::Path;
+use toml::Value;
+
+/// Front matter field with the number of an issue
+pub const ISSUE_FIELD: &str = "issue";
+
+#[derive(Args, Debug)]
+pub struct IssueArguments {
+    /// What the issue is about, put after the number in the title
+    title: Option<String>,
+
+    #[command(flatten)]
+    options: PostOptions,
+}
+
+/// Runs the `issue` subcommand, creating the next issue of a newsletter after
+/// the highest numbered one in the section
+pub fn run(args: IssueArguments, current_dir: &Path) -> Result<(), Error> {
+    let mut options = args.options;
+    let project = Project::load(current_dir, options.profile.clone())?;
+    let section = options.section.clone().or(project.config.section.clone());
+    let config = project.section_config(section.as_deref())?;
+
+    let dir = match &options.dir {
+        Some(dir) => Some(current_dir.join(dir)),
+        None => project
+            .content_dir(&config, project.preset)
+            .ok()
+            .map(|d| d.join(section.as_deref().unwrap_or_default())),
+    };
+    let mut dirs = dir.into_iter().collect::<Vec<_>>();
+    dirs.extend(
+        project
+            .drafts_dir(&config, project.preset)
+            .map(|d| d.join(section.as_deref().unwrap_or_default())),
+    );
+
+    let number = post::load_posts(&dirs)
+        .iter()
+        .filter_map(|p| post::field(&p.front_matter, ISSUE_FIELD)?.as_integer())
+        .max()
+        .unwrap_or(0)
+        + 1;
+
+    let title = match &args.title {
+        Some(title) => format!("Issue {}: {}", number, title),
+        None => format!("Issue {}", number),
+    };
+    options
+        .slug
+        .get_or_insert_with(|| format!("issue-{}", number));
+    options
+        .meta
+        .push((ISSUE_FIELD.to_string(), Value::Integer(number)));
+
+    create_post(&title, Vec::new(), "", None, None, options, current_dir)
+}
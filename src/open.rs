@@ -0,0 +1,109 @@
+use crate::collision::BUNDLE_INDEX;
+use crate::editor::{self, OpenWith, Opener, Pane};
+use crate::error::Error;
+use crate::post::{self, Post};
+use crate::project::Project;
+use crate::prompt;
+use crate::slug::create_safe_file_name;
+use clap::Args;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+#[derive(Args, Debug)]
+pub struct OpenArguments {
+    /// Slug or part of the title of the post
+    query: String,
+
+    /// Editor to open the post with
+    #[arg(short, long)]
+    editor: Option<String>,
+}
+
+/// Runs the `open` subcommand, opening the post matching the query
+pub fn run(args: OpenArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = &project.config;
+    let path = find_post(&project, &args.query, current_dir)?;
+
+    let opener = match config.open.unwrap_or_default() {
+        OpenWith::Editor => Opener::Editor {
+            command: editor::command_string(args.editor.or(config.editor.clone()))?,
+            detach: config.detach,
+            pane: config.pane.map(Pane::resolve).transpose()?,
+        },
+        OpenWith::System => Opener::System,
+    };
+    opener.open(&path)
+}
+
+/// The post whose slug is the query, or else whose slug or title contains it.
+/// The user picks one if there are several, when they can be asked.
+pub fn find_post(project: &Project, query: &str, current_dir: &Path) -> Result<PathBuf, Error> {
+    let config = &project.config;
+    let mut dirs = vec![project.content_dir(config, project.preset)?];
+    dirs.extend(project.drafts_dir(config, project.preset));
+    let posts = post::load_posts(&dirs);
+
+    let slug = create_safe_file_name(query, config.lang.as_deref(), &config.slug);
+    let query = query.to_lowercase();
+    let exact = posts
+        .iter()
+        .filter(|p| slug_of(p) == slug)
+        .collect::<Vec<_>>();
+    let matches = if exact.is_empty() {
+        posts
+            .iter()
+            .filter(|p| slug_of(p).contains(&slug) || p.title().to_lowercase().contains(&query))
+            .collect()
+    } else {
+        exact
+    };
+
+    let relative = |p: &Post| {
+        p.path
+            .strip_prefix(current_dir)
+            .unwrap_or(&p.path)
+            .display()
+            .to_string()
+    };
+    match matches.as_slice() {
+        [] => Err(Error::from_string(
+            format!("There's no post matching '{}'", query).as_str(),
+        )),
+        [post] => Ok(post.path.clone()),
+        _ => {
+            let options = matches
+                .iter()
+                .map(|p| format!("{}  {}", relative(p), p.title()))
+                .collect::<Vec<_>>();
+            if !prompt::is_interactive() {
+                return Err(Error::from_string(
+                    format!(
+                        "Several posts match '{}':\n  {}",
+                        query,
+                        options.join("\n  ")
+                    )
+                    .as_str(),
+                ));
+            }
+            let choice = prompt::choose("Which post?", &options)?;
+            Ok(matches[choice].path.clone())
+        }
+    }
+}
+
+// The slug of the post, from the front matter or its file name, which for a
+// bundle is its directory's
+fn slug_of(post: &Post) -> String {
+    if let Some(slug) = post::field(&post.front_matter, "slug").and_then(Value::as_str) {
+        return slug.to_string();
+    }
+
+    let path = match post.path.file_stem() {
+        Some(stem) if stem == BUNDLE_INDEX => post.path.parent().unwrap_or(&post.path),
+        _ => &post.path,
+    };
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,253 @@
+use crate::date::{self, Zone};
+use crate::document::between;
+use crate::error::Error;
+use chrono::{DateTime, FixedOffset};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Extensions of the files that are read as posts
+pub const POST_EXTENSIONS: [&str; 3] = ["md", "rst", "org"];
+
+/// Reads the front matter of an existing post, in any of the formats new
+/// posts can be written in
+pub fn read_front_matter(path: &Path) -> Result<Table, Error> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| Error::from_error(&format!("Failed to read '{}'", path.display()), &e))?;
+
+    parse_front_matter(&contents, is_rst(path)).ok_or(front_matter_error(path, &contents))
+}
+
+/// Whether the post is reStructuredText, whose fields aren't marked off from
+/// the rest of the file
+pub fn is_rst(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "rst")
+}
+
+/// The error for a post whose front matter can't be read, telling a post
+/// without any apart from one where it's broken
+pub fn front_matter_error(path: &Path, contents: &str) -> Error {
+    let contents = contents.trim_start_matches('\u{feff}');
+    let has_front_matter = ["+++", "---", "{", "#+"]
+        .iter()
+        .any(|m| contents.starts_with(m));
+    let message = if has_front_matter || is_rst(path) {
+        format!("Failed to parse the front matter of '{}'", path.display())
+    } else {
+        format!("There's no front matter in '{}'", path.display())
+    };
+    Error::from_string(&message)
+}
+
+/// Reads the front matter at the start of a post. Only RST posts have fields
+/// that aren't marked off, other files without delimiters have none.
+pub fn parse_front_matter(contents: &str, is_rst: bool) -> Option<Table> {
+    let contents = contents.trim_start_matches('\u{feff}');
+
+    if let Some((toml, _)) = between(contents, "+++") {
+        toml.parse().ok()
+    } else if let Some((yaml, _)) = between(contents, "---") {
+        match from_json(serde_yaml::from_str(yaml).ok()?)? {
+            Value::Table(table) => Some(table),
+            _ => None,
+        }
+    } else if contents.starts_with('{') {
+        let json = serde_json::Deserializer::from_str(contents)
+            .into_iter::<serde_json::Value>()
+            .next()?
+            .ok()?;
+        match from_json(json)? {
+            Value::Table(table) => Some(table),
+            _ => None,
+        }
+    } else if contents.starts_with("#+") {
+        Some(parse_org(contents))
+    } else if is_rst {
+        Some(parse_rst(contents))
+    } else {
+        None
+    }
+}
+
+/// An existing post and its front matter
+pub struct Post {
+    pub path: PathBuf,
+    pub front_matter: Table,
+}
+
+impl Post {
+    pub fn title(&self) -> &str {
+        field(&self.front_matter, "title")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    }
+
+    /// When the post is, or will be, published
+    pub fn date(&self, zone: Zone, date_format: Option<&str>) -> Option<DateTime<FixedOffset>> {
+        DATE_FIELDS
+            .iter()
+            .find_map(|f| field(&self.front_matter, f))
+            .and_then(|value| date::from_value(value, zone, date_format))
+    }
+}
+
+/// Fields with the publish date of a post, in order of precedence
+pub const DATE_FIELDS: [&str; 3] = ["publishDate", "date", "pubDate"];
+
+/// The posts in the directories, leaving out the ones that can't be read
+pub fn load_posts(dirs: &[PathBuf]) -> Vec<Post> {
+    dirs.iter()
+        .flat_map(|dir| find_posts(dir))
+        .filter_map(|path| {
+            let front_matter = read_front_matter(&path).ok()?;
+            Some(Post { path, front_matter })
+        })
+        .collect()
+}
+
+/// Every post in `dir` and its subdirectories, leaving out section index
+/// pages like `_index.md`
+pub fn find_posts(dir: &Path) -> Vec<PathBuf> {
+    let mut posts = Vec::new();
+    collect_posts(dir, &mut HashSet::new(), &mut posts);
+    posts.sort();
+    posts
+}
+
+// Symlinked directories are followed, `visited` has the real paths of the
+// directories already looked in so links back up the tree don't loop forever
+fn collect_posts(dir: &Path, visited: &mut HashSet<PathBuf>, posts: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    if !fs::canonicalize(dir).is_ok_and(|d| visited.insert(d)) {
+        return;
+    }
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_posts(&path, visited, posts);
+        } else if path
+            .extension()
+            .is_some_and(|e| POST_EXTENSIONS.iter().any(|p| e == *p))
+            && !path
+                .file_stem()
+                .is_some_and(|s| s.to_string_lossy().starts_with("_index"))
+        {
+            posts.push(path);
+        }
+    }
+}
+
+/// Looks a field up at the top level, and in the `extra` and `taxonomies`
+/// tables where Zola keeps them
+pub fn field<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    table.get(key).or_else(|| {
+        ["extra", "taxonomies"]
+            .iter()
+            .find_map(|t| table.get(*t)?.as_table()?.get(key))
+    })
+}
+
+/// The strings in a field that's either a string or a list of them
+pub fn field_strings(table: &Table, key: &str) -> Vec<String> {
+    match field(table, key) {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(a)) => a
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn from_json(value: serde_json::Value) -> Option<Value> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(b) => Some(Value::Boolean(b)),
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(Value::Integer)
+            .or_else(|| n.as_f64().map(Value::Float)),
+        serde_json::Value::String(s) => Some(Value::String(s)),
+        serde_json::Value::Array(a) => {
+            Some(Value::Array(a.into_iter().filter_map(from_json).collect()))
+        }
+        serde_json::Value::Object(o) => Some(Value::Table(
+            o.into_iter()
+                .filter_map(|(k, v)| Some((k, from_json(v)?)))
+                .collect(),
+        )),
+    }
+}
+
+// `#+KEYWORD: value` lines, with the ox-hugo prefixes taken off
+fn parse_org(contents: &str) -> Table {
+    let mut table = Table::new();
+
+    for line in contents.lines().take_while(|l| l.starts_with("#+")) {
+        let Some((keyword, value)) = line[2..].split_once(':') else {
+            continue;
+        };
+        let keyword = keyword.to_lowercase();
+        let value = value.trim();
+
+        match keyword.as_str() {
+            "filetags" => {
+                let tags = value
+                    .split(':')
+                    .filter(|t| !t.is_empty())
+                    .map(|t| Value::String(t.to_string()));
+                table.insert("tags".to_string(), Value::Array(tags.collect()));
+            }
+            "hugo_categories" => {
+                let categories = value
+                    .split_whitespace()
+                    .map(|c| Value::String(c.to_string()));
+                table.insert("categories".to_string(), Value::Array(categories.collect()));
+            }
+            "hugo_custom_front_matter" => {
+                if let Some((key, value)) = value.trim_start_matches(':').split_once(' ') {
+                    table.insert(key.to_string(), Value::String(value.to_string()));
+                }
+            }
+            _ => {
+                let key = keyword.strip_prefix("hugo_").unwrap_or(&keyword);
+                table.insert(key.to_string(), Value::String(value.to_string()));
+            }
+        }
+    }
+
+    table
+}
+
+// The title heading followed by a `:field: value` list, like Pelican reads
+fn parse_rst(contents: &str) -> Table {
+    let mut table = Table::new();
+    let mut lines = contents.lines().skip_while(|l| l.trim().is_empty());
+
+    if let Some(title) = lines.next().filter(|l| !l.starts_with(':')) {
+        table.insert("title".to_string(), Value::String(title.trim().to_string()));
+        lines.next();
+    }
+
+    for line in lines.skip_while(|l| l.trim().is_empty()) {
+        let Some((key, value)) = line.strip_prefix(':').and_then(|l| l.split_once(": ")) else {
+            break;
+        };
+        let value = match key {
+            "tags" | "category" | "categories" | "authors" => Value::Array(
+                value
+                    .split(',')
+                    .map(|v| Value::String(v.trim().to_string()))
+                    .collect(),
+            ),
+            _ => Value::String(value.trim().to_string()),
+        };
+        table.insert(key.to_string(), value);
+    }
+
+    table
+}
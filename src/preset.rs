@@ -0,0 +1,289 @@
+use crate::front_matter::{flatten_extra, Format, FrontMatter};
+use crate::hugo::SiteConfig;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::value::Datetime;
+use toml::{Table, Value};
+
+pub const DEFAULT_FILE_NAME_PATTERN: &str = "{slug}.{ext}";
+
+const ELEVENTY_CONFIG_FILES: [&str; 4] = [
+    ".eleventy.js",
+    "eleventy.config.js",
+    "eleventy.config.cjs",
+    "eleventy.config.mjs",
+];
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// Zola: tags under `[taxonomies]`, posts directly in `content/`
+    Zola,
+    /// Hugo: drafts with `lastmod` and `categories`, posts in `content/posts/`
+    Hugo,
+    /// Jekyll: YAML front matter, date prefixed posts in `_posts/`
+    Jekyll,
+    /// Eleventy: YAML front matter with `layout` and `permalink`, posts in `<input>/posts/`
+    Eleventy,
+    /// Astro: YAML front matter following the collection schema, posts in `src/content/<collection>/`
+    Astro,
+    /// Hexo: YAML front matter with `YYYY-MM-DD HH:mm:ss` dates, posts in `source/_posts/`
+    Hexo,
+}
+
+impl Preset {
+    /// Recognises a site generator from the files in the site's root directory
+    pub fn detect(root: &Path) -> Option<Preset> {
+        let has_file = |name: &str| root.join(name).is_file();
+        let has_file_with_extension = |stem: &str, extensions: &[&str]| {
+            extensions
+                .iter()
+                .any(|ext| has_file(&format!("{}.{}", stem, ext)))
+        };
+
+        if ELEVENTY_CONFIG_FILES.iter().any(|f| has_file(f)) {
+            Some(Preset::Eleventy)
+        } else if has_file_with_extension("astro.config", &["mjs", "js", "ts", "mts", "cjs"]) {
+            Some(Preset::Astro)
+        } else if root.join("scaffolds").is_dir() && root.join("source").is_dir() {
+            // Hexo also has a `_config.yml`, so it's told apart by its directories
+            Some(Preset::Hexo)
+        } else if has_file_with_extension("hugo", &["toml", "yaml", "yml", "json"]) {
+            Some(Preset::Hugo)
+        } else if has_file_with_extension("_config", &["yml", "yaml"]) {
+            Some(Preset::Jekyll)
+        } else if has_file("config.toml") {
+            // Older Hugo sites use `config.toml` too, but spell the base URL differently
+            let config = fs::read_to_string(root.join("config.toml")).unwrap_or_default();
+            if config.contains("baseURL") {
+                Some(Preset::Hugo)
+            } else {
+                Some(Preset::Zola)
+            }
+        } else {
+            None
+        }
+    }
+
+    pub fn default_format(&self) -> Format {
+        match self {
+            Preset::Zola | Preset::Hugo => Format::Toml,
+            Preset::Jekyll | Preset::Eleventy | Preset::Astro | Preset::Hexo => Format::Yaml,
+        }
+    }
+
+    /// Name of the directory posts are written to, unless the site's own
+    /// configuration says otherwise
+    pub fn content_directory(&self) -> &'static str {
+        match self {
+            Preset::Zola | Preset::Hugo => "content",
+            Preset::Jekyll => "_posts",
+            Preset::Eleventy => ".",
+            Preset::Astro => "src/content",
+            Preset::Hexo => "source/_posts",
+        }
+    }
+
+    /// Content directory set in the site generator's own configuration
+    pub fn site_content_directory(&self, root: &Path) -> Option<PathBuf> {
+        match self {
+            Preset::Eleventy => Some(eleventy_input_directory(root)),
+            Preset::Hugo => SiteConfig::read(root).content_dir.map(|d| root.join(d)),
+            Preset::Zola | Preset::Jekyll | Preset::Astro | Preset::Hexo => None,
+        }
+    }
+
+    /// Directory inside the content directory where new posts are placed,
+    /// for Astro this is the content collection
+    pub fn default_section(&self, root: &Path) -> Option<String> {
+        match self {
+            Preset::Zola | Preset::Jekyll | Preset::Hexo => None,
+            Preset::Hugo => SiteConfig::read(root)
+                .main_section
+                .or_else(|| Some("posts".to_string())),
+            Preset::Eleventy => Some("posts".to_string()),
+            Preset::Astro => Some("blog".to_string()),
+        }
+    }
+
+    /// Directory, relative to the site root, drafts are kept in separately
+    /// from the published posts
+    pub fn drafts_directory(&self) -> Option<&'static str> {
+        match self {
+            Preset::Zola | Preset::Hugo | Preset::Eleventy | Preset::Astro => None,
+            Preset::Jekyll => Some("_drafts"),
+            Preset::Hexo => Some("source/_drafts"),
+        }
+    }
+
+    /// Pattern for the name of new files, see [`crate::expand_file_name`]
+    pub fn file_name_pattern(&self) -> &'static str {
+        match self {
+            Preset::Zola | Preset::Hugo | Preset::Eleventy | Preset::Astro | Preset::Hexo => {
+                DEFAULT_FILE_NAME_PATTERN
+            }
+            Preset::Jekyll => "{date}-{slug}.{ext}",
+        }
+    }
+
+    /// Name of the field with the date the post was last updated
+    pub fn updated_field(&self) -> &'static str {
+        match self {
+            Preset::Zola | Preset::Jekyll | Preset::Eleventy | Preset::Hexo => "updated",
+            Preset::Hugo => "lastmod",
+            Preset::Astro => "updatedDate",
+        }
+    }
+
+    /// Name of the field with the old URLs that redirect to the post
+    pub fn aliases_field(&self) -> &'static str {
+        match self {
+            // From the jekyll-redirect-from plugin
+            Preset::Jekyll => "redirect_from",
+            Preset::Zola | Preset::Hugo | Preset::Eleventy | Preset::Astro | Preset::Hexo => {
+                "aliases"
+            }
+        }
+    }
+
+    /// Whether custom fields go in an `[extra]` table instead of next to the
+    /// others
+    pub fn nests_extra(&self) -> bool {
+        match self {
+            Preset::Zola => true,
+            Preset::Hugo | Preset::Jekyll | Preset::Eleventy | Preset::Astro | Preset::Hexo => {
+                false
+            }
+        }
+    }
+
+    /// Whether the post date includes the time of day
+    pub fn timestamps(&self) -> bool {
+        match self {
+            Preset::Zola | Preset::Jekyll | Preset::Eleventy | Preset::Astro => false,
+            Preset::Hugo | Preset::Hexo => true,
+        }
+    }
+
+    /// Fills in the fields the generator expects on a new post
+    pub fn prepare(&self, front_matter: &mut FrontMatter) {
+        match self {
+            Preset::Zola | Preset::Astro | Preset::Hexo => {}
+            Preset::Hugo => {
+                front_matter.updated = Some(front_matter.date);
+                front_matter.draft = Some(true);
+            }
+            Preset::Jekyll => {
+                front_matter
+                    .layout
+                    .get_or_insert_with(|| "post".to_string());
+            }
+            Preset::Eleventy => {
+                front_matter
+                    .layout
+                    .get_or_insert_with(|| "layouts/post.njk".to_string());
+                front_matter
+                    .permalink
+                    .get_or_insert_with(|| "/posts/{{ page.fileSlug }}/".to_string());
+            }
+        }
+    }
+
+    /// Moves the generic front matter keys to where the generator expects them
+    pub fn arrange(&self, table: &mut Table, taxonomies: &[String]) {
+        match self {
+            Preset::Zola => nest_taxonomies(table, taxonomies),
+            Preset::Hugo | Preset::Jekyll | Preset::Eleventy | Preset::Astro | Preset::Hexo => {
+                flatten_extra(table)
+            }
+        }
+
+        match self {
+            Preset::Zola | Preset::Eleventy => {}
+            Preset::Jekyll => rename_key(table, "aliases", self.aliases_field()),
+            Preset::Hugo => {
+                rename_key(table, "updated", self.updated_field());
+                if taxonomies.iter().any(|t| t == "categories") {
+                    table
+                        .entry("categories")
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                }
+            }
+            Preset::Astro => {
+                rename_key(table, "date", "pubDate");
+                rename_key(table, "updated", self.updated_field());
+            }
+            Preset::Hexo => {
+                for key in ["date", "updated"] {
+                    if let Some(value) = table.get_mut(key) {
+                        if let Value::Datetime(datetime) = *value {
+                            *value = hexo_date(&datetime);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Hexo parses `YYYY-MM-DD HH:mm:ss` in the site's timezone
+fn hexo_date(datetime: &Datetime) -> Value {
+    match (datetime.date, datetime.time) {
+        (Some(date), Some(time)) => Value::String(format!("{} {}", date, time)),
+        _ => Value::Datetime(*datetime),
+    }
+}
+
+/// Reads the input directory from the Eleventy configuration in `root`,
+/// falling back to Eleventy's default of the root itself
+pub fn eleventy_input_directory(root: &Path) -> PathBuf {
+    ELEVENTY_CONFIG_FILES
+        .iter()
+        .filter_map(|f| fs::read_to_string(root.join(f)).ok())
+        .find_map(|config| find_input_option(&config))
+        .map_or_else(|| root.to_path_buf(), |input| root.join(input))
+}
+
+// Looks for `input: "src"` in the `dir` option of the config, this is no
+// JavaScript parser but covers how the option is written in practice
+fn find_input_option(config: &str) -> Option<String> {
+    let after_key = &config[config.find("input")? + "input".len()..];
+    let value = after_key.trim_start().strip_prefix(':')?.trim_start();
+    let quote = value
+        .chars()
+        .next()
+        .filter(|c| ['"', '\'', '`'].contains(c))?;
+    let value = &value[1..];
+
+    Some(value[..value.find(quote)?].to_string())
+}
+
+/// Moves the taxonomies into a `[taxonomies]` table, like Zola reads them
+pub fn nest_taxonomies(table: &mut Table, taxonomies: &[String]) {
+    let mut nested = Table::new();
+    for taxonomy in taxonomies {
+        if let Some(terms) = table.remove(taxonomy) {
+            nested.insert(taxonomy.to_string(), terms);
+        }
+    }
+
+    if !nested.is_empty() {
+        table.insert("taxonomies".to_string(), Value::Table(nested));
+    }
+}
+
+// Rebuilds the table so the renamed key keeps its position
+fn rename_key(table: &mut Table, from: &str, to: &str) {
+    *table = std::mem::take(table)
+        .into_iter()
+        .map(|(k, v)| {
+            if k == from {
+                (to.to_string(), v)
+            } else {
+                (k, v)
+            }
+        })
+        .collect();
+}
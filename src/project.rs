@@ -0,0 +1,346 @@
+use crate::config::{self, Config};
+use crate::error::Error;
+use crate::preset::Preset;
+use crate::prompt;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+/// Files and directories that mark the root of a site, where the search for the
+/// content directory stops
+pub const ROOT_MARKERS: [&str; 1] = [".git"];
+
+// How many directories down the content directories of a monorepo's sites are
+// looked for
+const SITE_SEARCH_DEPTH: usize = 3;
+
+// Directories that never have content in them
+const SKIPPED_DIRECTORIES: [&str; 3] = ["node_modules", "target", "vendor"];
+
+/// The site new-post is run in, and its settings outside of any section
+pub struct Project {
+    /// Directory of the project config, or the current directory without one
+    pub root: PathBuf,
+    pub config_path: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub config: Config,
+    pub preset: Option<Preset>,
+    current_dir: PathBuf,
+}
+
+impl Project {
+    pub fn load(current_dir: &Path, profile: Option<String>) -> Result<Project, Error> {
+        let config_path = config::find_project_config(current_dir);
+        let profile = profile.or_else(|| env::var(config::PROFILE_VARIABLE).ok());
+        let config = Config::load(config_path.as_deref(), profile.as_deref(), None)?;
+        // Paths in the project config are relative to the directory it's in,
+        // without one the site's root is found by its generator's files
+        let root = match config_path.as_deref().and_then(Path::parent) {
+            Some(dir) => dir.to_path_buf(),
+            None => site_root(current_dir, &config).unwrap_or(current_dir.to_path_buf()),
+        };
+        let preset = config.preset.or_else(|| Preset::detect(&root));
+
+        Ok(Project {
+            root,
+            config_path,
+            profile,
+            config,
+            preset,
+            current_dir: current_dir.to_path_buf(),
+        })
+    }
+
+    /// The project of one of the sites in a monorepo, from the `sites` table of
+    /// the config
+    pub fn site(self, name: &str) -> Result<Project, Error> {
+        let path = self.config.sites.get(name).ok_or_else(|| {
+            let names = self.config.sites.keys().cloned().collect::<Vec<_>>();
+            Error::from_string(
+                format!(
+                    "There's no site named '{}' in the config, only {}",
+                    name,
+                    names.join(", ")
+                )
+                .as_str(),
+            )
+        })?;
+        let dir = self.root.join(config::expand_home(path));
+        if !dir.is_dir() {
+            return Err(Error::from_string(
+                format!("The directory of site '{}' doesn't exist", name).as_str(),
+            ));
+        }
+
+        let mut site = Project::load(&dir, self.profile)?;
+        // Without a config of its own, the site still has its own root
+        if site.config_path == self.config_path {
+            site.preset = site.config.preset.or_else(|| Preset::detect(&dir));
+            site.root = dir;
+        }
+        Ok(site)
+    }
+
+    /// The settings for posts in `section`
+    pub fn section_config(&self, section: Option<&str>) -> Result<Config, Error> {
+        Config::load(
+            self.config_path.as_deref(),
+            self.profile.as_deref(),
+            section,
+        )
+    }
+
+    /// Directory posts are written to, outside of any section
+    pub fn content_dir(&self, config: &Config, preset: Option<Preset>) -> Result<PathBuf, Error> {
+        self.find_content_dir(config, preset)?.ok_or_else(|| {
+            Error::from_string(
+                format!(
+                    "Failed to find a directory named '{}'",
+                    content_dir_names(config, preset).join("' or '")
+                )
+                .as_str(),
+            )
+        })
+    }
+
+    // The content directory, `None` if there's none to be found
+    fn find_content_dir(
+        &self,
+        config: &Config,
+        preset: Option<Preset>,
+    ) -> Result<Option<PathBuf>, Error> {
+        // The content directory is looked for from where new-post is run, so
+        // the site it's run in is picked in a monorepo, unless that's outside
+        // the project
+        let search_dir = if self.current_dir.starts_with(&self.root) {
+            &self.current_dir
+        } else {
+            &self.root
+        };
+
+        let markers = match &config.root_markers {
+            Some(markers) => markers.iter().map(String::as_str).collect(),
+            None => ROOT_MARKERS.to_vec(),
+        };
+
+        let names = content_dir_names(config, preset);
+
+        match (&config.content_dir, preset) {
+            (Some(content_dir), _) => Ok(Some(self.root.join(config::expand_home(content_dir)))),
+            (None, Some(preset)) if config.content_dirs.is_none() => {
+                match preset.site_content_directory(&self.root) {
+                    Some(content_dir) => Ok(Some(content_dir)),
+                    None => locate_content_directory(search_dir, &names, &markers),
+                }
+            }
+            (None, _) => locate_content_directory(search_dir, &names, &markers),
+        }
+    }
+
+    /// Like [`Project::content_dir`], but a missing content directory is created
+    /// when `create` is set, or if the user says so when asked
+    pub fn content_dir_or_create(
+        &self,
+        config: &Config,
+        preset: Option<Preset>,
+        create: bool,
+    ) -> Result<PathBuf, Error> {
+        // Only a missing content directory is created, not one of several
+        if let Some(content_dir) = self.find_content_dir(config, preset)? {
+            return Ok(content_dir);
+        }
+
+        let search_dir = match self.config_path {
+            Some(_) => &self.root,
+            None => &self.current_dir,
+        };
+        let content_dir = search_dir.join(content_dir_names(config, preset)[0]);
+        let question = format!(
+            "There's no content directory. Create '{}'?",
+            content_dir.display()
+        );
+        if !create && !prompt::confirm(&question, false)? {
+            return self.content_dir(config, preset);
+        }
+
+        fs::create_dir_all(&content_dir)
+            .map_err(|e| Error::from_error("Failed to create the content directory", &e))?;
+        Ok(content_dir)
+    }
+
+    /// Directory drafts are kept in, if they're kept apart from other posts
+    pub fn drafts_dir(&self, config: &Config, preset: Option<Preset>) -> Option<PathBuf> {
+        match (&config.drafts_dir, preset) {
+            (Some(drafts_dir), _) => Some(self.root.join(config::expand_home(drafts_dir))),
+            (None, Some(preset)) => preset.drafts_directory().map(|d| self.root.join(d)),
+            (None, None) => None,
+        }
+    }
+}
+
+// The closest directory with a site generator's files in it, from the current
+// one up to the root of the site, marked by one of the root markers
+fn site_root(current_dir: &Path, config: &Config) -> Option<PathBuf> {
+    let markers = match &config.root_markers {
+        Some(markers) => markers.iter().map(String::as_str).collect(),
+        None => ROOT_MARKERS.to_vec(),
+    };
+
+    for dir in current_dir.ancestors() {
+        if Preset::detect(dir).is_some() {
+            return Some(dir.to_path_buf());
+        }
+        if markers.iter().any(|m| dir.join(m).exists()) {
+            break;
+        }
+    }
+    None
+}
+
+// Names the content directory may have, most likely first
+fn content_dir_names(config: &Config, preset: Option<Preset>) -> Vec<&str> {
+    match (&config.content_dirs, preset) {
+        (Some(names), _) if !names.is_empty() => names.iter().map(String::as_str).collect(),
+        (_, Some(preset)) => vec![preset.content_directory()],
+        (_, None) => vec!["content"],
+    }
+}
+
+// Looks for a content directory with one of the names, in order, in and below
+// each directory from the current one up to the root of the site, marked by one
+// of `markers`. `None` if there's none.
+fn locate_content_directory(
+    current_dir: &Path,
+    names: &[&str],
+    markers: &[&str],
+) -> Result<Option<PathBuf>, Error> {
+    for dir in current_dir.ancestors() {
+        for name in names {
+            if let Some(content_dir) = content_directory_in(dir, name)? {
+                return Ok(Some(content_dir));
+            }
+        }
+        if markers.iter().any(|m| dir.join(m).exists()) {
+            break;
+        }
+    }
+
+    // A monorepo may have the content directories of several sites further down
+    let mut found = Vec::new();
+    let mut visited = HashSet::new();
+    content_directories_below(
+        current_dir,
+        names,
+        SITE_SEARCH_DEPTH,
+        &mut visited,
+        &mut found,
+    );
+    match found.len() {
+        0 => Ok(None),
+        1 => Ok(Some(found.remove(0))),
+        _ if prompt::is_interactive() => {
+            let options = found
+                .iter()
+                .map(|d| {
+                    d.strip_prefix(current_dir)
+                        .unwrap_or(d)
+                        .display()
+                        .to_string()
+                })
+                .collect::<Vec<_>>();
+            let choice =
+                prompt::choose("Which content directory should the post go in?", &options)?;
+            Ok(Some(found.remove(choice)))
+        }
+        _ => Err(Error::from_string(
+            format!(
+                "Found several content directories, pick one with --site or --dir: {}",
+                found
+                    .iter()
+                    .map(|d| d.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .as_str(),
+        )),
+    }
+}
+
+// Collects the content directories in the subdirectories of `dir`, down to
+// `depth` levels, leaving out hidden and dependency directories. Symlinked
+// directories are followed, `visited` has the real paths of the directories
+// already looked in so links back up the tree aren't followed around.
+fn content_directories_below(
+    dir: &Path,
+    names: &[&str],
+    depth: usize,
+    visited: &mut HashSet<PathBuf>,
+    found: &mut Vec<PathBuf>,
+) {
+    if depth == 0 || !fs::canonicalize(dir).is_ok_and(|d| visited.insert(d)) {
+        return;
+    }
+
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    let mut children = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter(|p| {
+            p.file_name()
+                .and_then(OsStr::to_str)
+                .is_some_and(|n| !n.starts_with('.') && !SKIPPED_DIRECTORIES.contains(&n))
+        })
+        .collect::<Vec<_>>();
+    children.sort();
+
+    for child in children {
+        match names
+            .iter()
+            .find_map(|n| content_directory_in(&child, n).ok().flatten())
+        {
+            Some(content_dir) => found.push(content_dir),
+            None => content_directories_below(&child, names, depth - 1, visited, found),
+        }
+    }
+}
+
+fn content_directory_in(dir: &Path, directory_name: &str) -> Result<Option<PathBuf>, Error> {
+    if dir.ends_with(directory_name) {
+        return Ok(Some(dir.to_path_buf()));
+    }
+
+    // The name may be nested, e.g. `src/content`, so descend one level at a time
+    let mut content_dir = dir.to_path_buf();
+    for name in Path::new(directory_name) {
+        match find_child_directory(&content_dir, name)? {
+            Some(child) => content_dir = child,
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(content_dir))
+}
+
+fn find_child_directory(parent: &Path, name: &OsStr) -> Result<Option<PathBuf>, Error> {
+    Ok(parent
+        .read_dir()
+        .map_err(|e| Error::from_error("Failed to get children of current working directory", &e))?
+        .filter_map(|c| {
+            if let Ok(de) = c {
+                // Follows symlinks, unlike the entry's file type
+                if de.path().is_dir() {
+                    Some(de)
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        })
+        .find(|dir| dir.file_name() == name)
+        .map(|de| de.path()))
+}
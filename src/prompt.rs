@@ -0,0 +1,99 @@
+use crate::error::Error;
+use std::io::{stderr, stdin, IsTerminal, Write};
+
+/// Whether there's a user at the terminal to answer questions
+pub fn is_interactive() -> bool {
+    stdin().is_terminal() && stderr().is_terminal()
+}
+
+/// Asks the user a question on the terminal and returns the trimmed answer
+pub fn ask(question: &str) -> Result<String, Error> {
+    eprint!("{} ", question);
+    stderr()
+        .flush()
+        .map_err(|e| Error::from_error("Failed to write to the terminal", &e))?;
+
+    let mut answer = String::new();
+    stdin()
+        .read_line(&mut answer)
+        .map_err(|e| Error::from_error("Failed to read answer", &e))?;
+
+    Ok(answer.trim().to_string())
+}
+
+/// Asks a yes or no question, an empty answer is `default`. Without a user to
+/// ask the answer is no.
+pub fn confirm(question: &str, default: bool) -> Result<bool, Error> {
+    if !is_interactive() {
+        return Ok(false);
+    }
+
+    let options = if default { "[Y/n]" } else { "[y/N]" };
+    let answer = ask(&format!("{} {}", question, options))?.to_lowercase();
+    Ok(match answer.as_str() {
+        "" => default,
+        answer => matches!(answer, "y" | "yes"),
+    })
+}
+
+/// Asks the user to pick one of the options by its number, and returns its
+/// index
+pub fn choose(question: &str, options: &[String]) -> Result<usize, Error> {
+    eprintln!("{}", question);
+    for (i, option) in options.iter().enumerate() {
+        eprintln!("  {}) {}", i + 1, option);
+    }
+
+    let answer = ask(">")?;
+    answer
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=options.len()).contains(n))
+        .map(|n| n - 1)
+        .ok_or(Error::from_string(
+            format!("'{}' isn't one of the options", answer).as_str(),
+        ))
+}
+
+/// Asks the user to pick any number of the options by their numbers, or to
+/// write new ones, separated by commas or spaces
+pub fn pick(question: &str, options: &[String]) -> Result<Vec<String>, Error> {
+    eprintln!("{}", question);
+    for (i, option) in options.iter().enumerate() {
+        eprintln!("  {}) {}", i + 1, option);
+    }
+
+    let answer = ask(">")?;
+    let mut picked = Vec::new();
+    for word in words(&answer) {
+        let option = match word.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => options[n - 1].clone(),
+            Ok(_) | Err(_) => word.to_string(),
+        };
+        if !picked.contains(&option) {
+            picked.push(option);
+        }
+    }
+    Ok(picked)
+}
+
+/// Asks a question with a suggested answer, which an empty answer accepts
+pub fn suggest(question: &str, suggestion: &str) -> Result<String, Error> {
+    let answer = ask(&format!("{} [{}]", question, suggestion))?;
+    Ok(if answer.is_empty() {
+        suggestion.to_string()
+    } else {
+        answer
+    })
+}
+
+/// The words of an answer, separated by commas if there are any and by spaces
+/// otherwise
+pub fn words(answer: &str) -> Vec<&str> {
+    let words = if answer.contains(',') {
+        answer.split(',').map(str::trim).collect::<Vec<_>>()
+    } else {
+        answer.split_whitespace().collect()
+    };
+    words.into_iter().filter(|w| !w.is_empty()).collect()
+}
@@ -0,0 +1,226 @@
+use crate::collision::BUNDLE_INDEX;
+use crate::date::{self, Zone};
+use crate::document::Document;
+use crate::error::Error;
+use crate::front_matter::Extension;
+use crate::post::{self, DATE_FIELDS};
+use crate::preset::DEFAULT_FILE_NAME_PATTERN;
+use crate::project::Project;
+use crate::{expand_file_name, reading_time, touch, url};
+use clap::{Args, ValueEnum};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Field with the date an unpublished post was first published
+pub const FIRST_PUBLISHED_FIELD: &str = "first_published";
+
+#[derive(Args, Debug)]
+pub struct PublishArguments {
+    /// Post to publish
+    post: PathBuf,
+
+    /// Leave a post in the drafts directory where it is
+    #[arg(long)]
+    in_place: bool,
+}
+
+/// Runs the `publish` subcommand, marking a draft as published today and
+/// moving it out of the drafts directory, then printing its URL
+pub fn run(args: PublishArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = &project.config;
+    let preset = project.preset;
+    let path = current_dir.join(&args.post);
+
+    let mut document = Document::read(&path)?;
+    let table = document.table();
+    if post::field(&table, "draft").is_some() {
+        document.set("draft", Value::Boolean(false), "");
+    }
+    let date_field = DATE_FIELDS
+        .into_iter()
+        .find(|k| table.contains_key(*k))
+        .unwrap_or("date");
+    let zone = Zone::from_setting(config.timezone.as_deref())?;
+    let now = zone.now();
+    let date = touch::date_value(table.get(date_field), now, config.date_format.as_deref())?;
+    document.set(date_field, date, "title");
+    if config.reading_time {
+        reading_time::write(&mut document, preset);
+    }
+
+    let content_dir = project.content_dir(config, preset)?;
+    let drafts_dir = project.drafts_dir(config, preset);
+    let in_drafts = drafts_dir
+        .as_ref()
+        .and_then(|d| fs::canonicalize(d).ok())
+        .zip(fs::canonicalize(&path).ok())
+        .and_then(|(drafts_dir, path)| Some(path.strip_prefix(&drafts_dir).ok()?.to_path_buf()));
+
+    let moves = match in_drafts {
+        Some(relative) if !args.in_place => {
+            // Bundles are moved with the files next to the post
+            let is_bundle = path.file_stem().is_some_and(|s| s == BUNDLE_INDEX);
+            let (from, to) = if is_bundle {
+                let dir = relative.parent().unwrap_or(Path::new(""));
+                (
+                    path.parent().unwrap_or(&path).to_path_buf(),
+                    content_dir.join(dir),
+                )
+            } else {
+                let pattern = config
+                    .filename
+                    .as_deref()
+                    .or(preset.map(|p| p.file_name_pattern()))
+                    .unwrap_or(DEFAULT_FILE_NAME_PATTERN);
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let extension = path
+                    .extension()
+                    .and_then(|e| Extension::from_str(&e.to_string_lossy(), true).ok())
+                    .unwrap_or_default();
+                let file_name = expand_file_name(pattern, &stem, now.date_naive(), extension);
+                let dir = relative.parent().unwrap_or(Path::new(""));
+                (path.clone(), content_dir.join(dir).join(file_name))
+            };
+            Some((from, to, is_bundle))
+        }
+        Some(_) | None => None,
+    };
+
+    // Checked before the post is written, so it's left as it was
+    if let Some((_, to, _)) = &moves {
+        if to.exists() {
+            return Err(Error::from_string(
+                format!("'{}' already exists", to.display()).as_str(),
+            ));
+        }
+    }
+    document.write()?;
+    let path = match moves {
+        Some((from, to, true)) => {
+            move_to(&from, &to)?;
+            to.join(path.file_name().unwrap_or_default())
+        }
+        Some((from, to, false)) => {
+            move_to(&from, &to)?;
+            to
+        }
+        None => path,
+    };
+
+    let front_matter = post::read_front_matter(&path)?;
+    println!(
+        "{}",
+        url::post_url(
+            config,
+            &project.root,
+            preset,
+            &content_dir,
+            &path,
+            &front_matter
+        )
+    );
+    Ok(())
+}
+
+#[derive(Args, Debug)]
+pub struct UnpublishArguments {
+    /// Post to turn back into a draft
+    post: PathBuf,
+
+    /// Move the post into the drafts directory
+    #[arg(long)]
+    to_drafts: bool,
+}
+
+/// Runs the `unpublish` subcommand, marking a post as a draft again and keeping
+/// its date in `first_published`
+pub fn unpublish(args: UnpublishArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = &project.config;
+    let preset = project.preset;
+    let path = current_dir.join(&args.post);
+
+    let mut document = Document::read(&path)?;
+    let table = document.table();
+    let date_field = DATE_FIELDS
+        .into_iter()
+        .find(|k| table.contains_key(*k))
+        .unwrap_or("date");
+    document.set("draft", Value::Boolean(true), date_field);
+    if let (Some(date), None) = (
+        table.get(date_field),
+        post::field(&table, FIRST_PUBLISHED_FIELD),
+    ) {
+        document.set_custom(FIRST_PUBLISHED_FIELD, date.clone(), preset);
+    }
+    if !args.to_drafts {
+        return document.write();
+    }
+
+    let Some(drafts_dir) = project.drafts_dir(config, preset) else {
+        return Err(Error::from_string(
+            "There's no drafts directory to move the post to, set `drafts_dir` in the config",
+        ));
+    };
+    let content_dir = project.content_dir(config, preset)?;
+    let relative = path.strip_prefix(&content_dir).map_err(|_| {
+        Error::from_string(
+            format!(
+                "'{}' isn't in the content directory '{}'",
+                path.display(),
+                content_dir.display()
+            )
+            .as_str(),
+        )
+    })?;
+
+    let is_bundle = path.file_stem().is_some_and(|s| s == BUNDLE_INDEX);
+    let (from, to) = if is_bundle {
+        let dir = relative.parent().unwrap_or(Path::new(""));
+        (
+            path.parent().unwrap_or(&path).to_path_buf(),
+            drafts_dir.join(dir),
+        )
+    } else {
+        // Drafts aren't dated yet, so a date in front of the name goes
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let undated =
+            date::split_dated_name(&file_name).map_or(file_name.as_ref(), |(_, rest)| rest);
+        (
+            path.clone(),
+            drafts_dir.join(relative.with_file_name(undated)),
+        )
+    };
+    // Checked before the post is written, so it's left as it was
+    if to.exists() {
+        return Err(Error::from_string(
+            format!("'{}' already exists", to.display()).as_str(),
+        ));
+    }
+    document.write()?;
+    move_to(&from, &to)?;
+    println!("{}", to.display());
+    Ok(())
+}
+
+/// Moves a file or directory, creating the directories it's moved into
+pub fn move_to(from: &Path, to: &Path) -> Result<(), Error> {
+    if to.exists() {
+        return Err(Error::from_string(
+            format!("'{}' already exists", to.display()).as_str(),
+        ));
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| Error::from_error("Failed to create the post's directory", &e))?;
+    }
+
+    fs::rename(from, to).map_err(|e| {
+        Error::from_error(
+            &format!("Failed to move '{}' to '{}'", from.display(), to.display()),
+            &e,
+        )
+    })
+}
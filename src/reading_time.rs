@@ -0,0 +1,48 @@
+use crate::document::Document;
+use crate::error::Error;
+use crate::preset::Preset;
+use crate::project::Project;
+use clap::Args;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Average reading speed the estimate is based on
+pub const WORDS_PER_MINUTE: usize = 200;
+
+#[derive(Args, Debug)]
+pub struct ReadingTimeArguments {
+    /// Post to estimate the reading time of
+    post: PathBuf,
+}
+
+/// Runs the `reading-time` subcommand, writing the post's reading time to its
+/// front matter
+pub fn run(args: ReadingTimeArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let mut document = Document::read(&current_dir.join(&args.post))?;
+
+    let minutes = write(&mut document, project.preset);
+    println!("{} min", minutes);
+    document.write()
+}
+
+/// Sets `extra.reading_time` to the minutes it takes to read the body,
+/// returning them
+pub fn write(document: &mut Document, preset: Option<Preset>) -> usize {
+    let minutes = estimate(document.body());
+    let value = Value::Integer(minutes as i64);
+
+    document.set_custom("reading_time", value, preset);
+    minutes
+}
+
+/// Minutes it takes to read `body`, rounded up, and at least one
+pub fn estimate(body: &str) -> usize {
+    // Leaves out markup like `#`, `-` and `---`
+    let words = body
+        .split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .count();
+
+    words.div_ceil(WORDS_PER_MINUTE).max(1)
+}
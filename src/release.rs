@@ -0,0 +1,85 @@
+use crate::error::Error;
+use crate::git;
+use crate::{create_post, PostOptions};
+use clap::Args;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Headings of the changelog, and the words commit subjects under them start with
+const CHANGELOG: [(&str, &[&str]); 3] = [
+    ("Added", &["add", "new", "feat"]),
+    ("Changed", &[]),
+    ("Fixed", &["fix", "bug"]),
+];
+
+#[derive(Args, Debug)]
+pub struct ReleaseArguments {
+    /// Version that's released, e.g. `1.4.0`
+    version: String,
+
+    /// Fill in the changelog with the subjects of the commits since the last
+    /// tag, in the repository at the path or the current directory
+    #[arg(long, value_name = "REPOSITORY", num_args = 0..=1, default_missing_value = ".")]
+    commits: Option<PathBuf>,
+
+    #[command(flatten)]
+    options: PostOptions,
+}
+
+/// Runs the `release` subcommand, creating release notes for a version with a
+/// changelog to fill in
+pub fn run(args: ReleaseArguments, current_dir: &Path) -> Result<(), Error> {
+    let subjects = match &args.commits {
+        Some(repository) => {
+            let repository = current_dir.join(repository);
+            let since = git::last_tag(&repository);
+            git::commit_subjects(&repository, since.as_deref())?
+        }
+        None => Vec::new(),
+    };
+
+    let mut options = args.options;
+    options
+        .meta
+        .push(("version".to_string(), Value::String(args.version.clone())));
+    let title = format!("Release {}", args.version);
+    create_post(
+        &title,
+        Vec::new(),
+        &changelog(&subjects),
+        None,
+        None,
+        options,
+        current_dir,
+    )
+}
+
+// The changelog's headings with the commits sorted under them by the first
+// word of their subjects, the ones that don't fit anywhere else are changes
+fn changelog(subjects: &[String]) -> String {
+    let heading_of = |subject: &str| {
+        let word = subject
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        CHANGELOG
+            .iter()
+            .position(|(_, words)| words.iter().any(|w| word.starts_with(w)))
+            .unwrap_or(1)
+    };
+
+    let mut body = String::new();
+    for (i, (heading, _)) in CHANGELOG.iter().enumerate() {
+        body.push_str(&format!("\n## {}\n", heading));
+        let items = subjects
+            .iter()
+            .filter(|s| heading_of(s) == i)
+            .map(|s| format!("- {}\n", s))
+            .collect::<String>();
+        if !items.is_empty() {
+            body.push_str(&format!("\n{}", items));
+        }
+    }
+    body
+}
@@ -0,0 +1,160 @@
+use crate::collision::BUNDLE_INDEX;
+use crate::document::Document;
+use crate::error::Error;
+use crate::project::Project;
+use crate::publish::move_to;
+use crate::rename::is_published;
+use crate::url;
+use clap::Args;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct MoveArguments {
+    /// Post to move
+    post: PathBuf,
+
+    /// Section to move it to, e.g. `notes` or `blog/2024`
+    section: String,
+}
+
+/// Runs the `move` subcommand, moving a post, or its bundle, to another section
+/// and keeping its old URL as an alias
+pub fn run(args: MoveArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let new_path = move_post(&project, &current_dir.join(&args.post), &args.section)?;
+    println!("{}", new_path.display());
+    Ok(())
+}
+
+/// Moves a post, or its bundle, to `section` and returns its new path. Drafts
+/// stay in the drafts directory.
+pub fn move_post(project: &Project, path: &Path, section: &str) -> Result<PathBuf, Error> {
+    let config = &project.config;
+    let preset = project.preset;
+    let content_dir = project.content_dir(config, preset)?;
+    let base_dir = match project.drafts_dir(config, preset) {
+        Some(drafts_dir) if path.starts_with(&drafts_dir) => drafts_dir,
+        Some(_) | None => content_dir.clone(),
+    };
+    let is_bundle = path.file_stem().is_some_and(|s| s == BUNDLE_INDEX);
+    let from = if is_bundle {
+        path.parent().unwrap_or(path).to_path_buf()
+    } else {
+        path.to_path_buf()
+    };
+    let new_dir = base_dir.join(section);
+    let to = new_dir.join(from.file_name().unwrap_or_default());
+    // Checked before anything is changed, so the post is left as it was
+    if to.exists() {
+        return Err(Error::from_string(
+            format!("'{}' already exists", to.display()).as_str(),
+        ));
+    }
+    fs::create_dir_all(&new_dir)
+        .map_err(|e| Error::from_error("Failed to create the section's directory", &e))?;
+
+    let mut document = Document::read(path)?;
+    let table = document.table();
+    if is_published(project, path, &table) {
+        let old_path = url::post_path(preset, &content_dir, path, &table);
+        url::add_alias(&mut document, preset, &old_path);
+    }
+    // Links to files that stay behind are pointed at them from the new place
+    let old_dir = path.parent().unwrap_or(Path::new(""));
+    let post_dir = if is_bundle { to.clone() } else { new_dir };
+    let body = rewrite_links(document.body(), |target| {
+        let file = fs::canonicalize(old_dir.join(target)).ok()?;
+        if is_bundle && file.starts_with(fs::canonicalize(&from).ok()?) {
+            return None;
+        }
+        let dir = fs::canonicalize(post_dir.parent()?)
+            .ok()?
+            .join(post_dir.file_name()?);
+        Some(
+            relative_path(&dir, &file)
+                .to_string_lossy()
+                .replace('\\', "/"),
+        )
+    });
+    document.set_body(body);
+
+    move_to(&from, &to)?;
+    let new_path = if is_bundle {
+        to.join(path.file_name().unwrap_or_default())
+    } else {
+        to
+    };
+    document.set_path(new_path.clone());
+    document.write()?;
+    Ok(new_path)
+}
+
+// Replaces the relative targets of Markdown links and images, `](target)`,
+// with what `replace` gives for them
+fn rewrite_links(body: &str, replace: impl Fn(&str) -> Option<String>) -> String {
+    let mut rewritten = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find("](") {
+        let (before, after) = rest.split_at(start + 2);
+        rewritten.push_str(before);
+
+        let end = after
+            .find(|c: char| c == ')' || c.is_whitespace())
+            .unwrap_or(after.len());
+        let target = &after[..end];
+        let is_relative = !target.is_empty()
+            && !target.starts_with(['/', '#'])
+            && !target.contains("://")
+            && !target.starts_with("mailto:");
+        match replace(target).filter(|_| is_relative) {
+            Some(new_target) => rewritten.push_str(&new_target),
+            None => rewritten.push_str(target),
+        }
+        rest = &after[end..];
+    }
+
+    rewritten.push_str(rest);
+    rewritten
+}
+
+// The path from the directory `from` to `to`, both absolute
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    from[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .chain(to[common..].iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_post_alone_when_destination_exists() {
+        let root = std::env::temp_dir().join(format!("new-post-move-{}", std::process::id()));
+        let content = root.join("content");
+        fs::create_dir_all(content.join("notes")).unwrap();
+        fs::write(
+            root.join("config.toml"),
+            "base_url = \"https://example.com\"\n",
+        )
+        .unwrap();
+        let post = "+++\ntitle = \"Post\"\n+++\n![](../pic.png)\n";
+        fs::write(content.join("post.md"), post).unwrap();
+        fs::write(content.join("notes/post.md"), "").unwrap();
+
+        let project = Project::load(&root, None).unwrap();
+        let moved = move_post(&project, &content.join("post.md"), "notes");
+        let left = fs::read_to_string(content.join("post.md")).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(moved.is_err());
+        assert_eq!(left, post);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::collision::BUNDLE_INDEX;
+use crate::date;
+use crate::document::Document;
+use crate::error::Error;
+use crate::post;
+use crate::project::Project;
+use crate::publish::move_to;
+use crate::slug::create_safe_file_name;
+use crate::url;
+use clap::Args;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+#[derive(Args, Debug)]
+pub struct RenameArguments {
+    /// Post to rename
+    post: PathBuf,
+
+    /// New title of the post
+    title: String,
+}
+
+/// Runs the `rename` subcommand, changing the title of a post and its file name
+/// to match, and adding the old URL to its aliases
+pub fn run(args: RenameArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = &project.config;
+    let preset = project.preset;
+    let path = current_dir.join(&args.post);
+
+    let mut document = Document::read(&path)?;
+    let table = document.table();
+    let lang = post::field(&table, "lang")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .or(config.lang.clone());
+    let slug = create_safe_file_name(&args.title, lang.as_deref(), &config.slug);
+    if slug.is_empty() {
+        return Err(Error::from_string(
+            format!("The slug made from the title '{}' is empty", args.title).as_str(),
+        ));
+    }
+
+    if is_published(&project, &path, &table) {
+        let content_dir = project.content_dir(config, preset)?;
+        let old_path = url::post_path(preset, &content_dir, &path, &table);
+        url::add_alias(&mut document, preset, &old_path);
+    }
+    document.set("title", Value::String(args.title.clone()), "");
+    if table.contains_key("slug") {
+        document.set("slug", Value::String(slug.clone()), "title");
+    }
+
+    // A bundle is renamed by its directory
+    let is_bundle = path.file_stem().is_some_and(|s| s == BUNDLE_INDEX);
+    let from = if is_bundle {
+        path.parent().unwrap_or(&path)
+    } else {
+        &path
+    };
+    let file_name = from.file_name().unwrap_or_default().to_string_lossy();
+    let to = from.with_file_name(renamed(&file_name, &slug, is_bundle));
+    // Moved before it's written, so nothing changes if it can't be
+    if to != from {
+        move_to(from, &to)?;
+    }
+
+    let new_path = if is_bundle {
+        to.join(path.file_name().unwrap_or_default())
+    } else {
+        to
+    };
+    document.set_path(new_path.clone());
+    document.write()?;
+    println!("{}", new_path.display());
+    Ok(())
+}
+
+/// Whether the post has been out on the site, so its URL may be linked to
+pub fn is_published(project: &Project, path: &Path, table: &toml::Table) -> bool {
+    let is_draft = post::field(table, "draft").and_then(Value::as_bool) == Some(true);
+    let in_drafts = project
+        .drafts_dir(&project.config, project.preset)
+        .is_some_and(|d| path.starts_with(d));
+    !is_draft && !in_drafts
+}
+
+// The file name with the slug swapped for the new one, keeping a date in front
+// like Jekyll's and the language and extension after it
+fn renamed(file_name: &str, slug: &str, is_bundle: bool) -> String {
+    let rest = date::split_dated_name(file_name).map_or(file_name, |(_, rest)| rest);
+    let date = &file_name[..file_name.len() - rest.len()];
+    let suffix = if is_bundle {
+        ""
+    } else {
+        rest.find('.').map_or("", |i| &rest[i..])
+    };
+
+    format!("{}{}{}", date, slug, suffix)
+}
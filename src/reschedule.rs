@@ -0,0 +1,84 @@
+use crate::date::{self, Zone};
+use crate::document::Document;
+use crate::error::Error;
+use crate::front_matter::to_toml_datetime;
+use crate::list::relative_day;
+use crate::post::DATE_FIELDS;
+use crate::project::Project;
+use crate::publish::move_to;
+use crate::touch;
+use chrono::NaiveTime;
+use clap::Args;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+#[derive(Args, Debug)]
+pub struct RescheduleArguments {
+    /// Post to move to another date
+    post: PathBuf,
+
+    /// New date, like `--date` takes, e.g. `next monday 9:00` or `2024-06-01`
+    date: String,
+}
+
+/// Runs the `reschedule` subcommand, changing the date of a post and printing
+/// when it's now published
+pub fn run(args: RescheduleArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = &project.config;
+    let zone = Zone::from_setting(config.timezone.as_deref())?;
+    let post_date = date::parse_date(&args.date, zone)?;
+    let path = current_dir.join(&args.post);
+
+    let mut document = Document::read(&path)?;
+    let table = document.table();
+    let date_field = DATE_FIELDS
+        .into_iter()
+        .find(|k| table.contains_key(*k))
+        .unwrap_or("date");
+    let existing = table.get(date_field);
+
+    // The time of day stays as it was, unless a new one is given
+    let old = existing.and_then(|v| date::from_value(v, zone, config.date_format.as_deref()));
+    let time = post_date
+        .time
+        .or(old.map(|d| d.time()))
+        .unwrap_or(NaiveTime::MIN);
+    let when = zone.at(post_date.date, time);
+    let mut value = touch::date_value(existing, when, config.date_format.as_deref())?;
+    if let (Some(_), Value::Datetime(date)) = (post_date.time, &value) {
+        if date.time.is_none() {
+            value = Value::Datetime(to_toml_datetime(when));
+        }
+    }
+    document.set(date_field, value, "title");
+    document.write()?;
+
+    // Names like Jekyll's start with the date
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let path = match date::split_dated_name(&file_name) {
+        Some((_, rest)) => {
+            let new_path =
+                path.with_file_name(format!("{}-{}", post_date.date.format("%Y-%m-%d"), rest));
+            if new_path != path {
+                move_to(&path, &new_path)?;
+            }
+            new_path
+        }
+        None => path,
+    };
+
+    let days = (post_date.date - zone.now().date_naive()).num_days();
+    let format = if post_date.time.is_some() || old.is_some_and(|d| d.time() != NaiveTime::MIN) {
+        "%a %Y-%m-%d %H:%M"
+    } else {
+        "%a %Y-%m-%d"
+    };
+    println!(
+        "{}  {}, {}",
+        path.strip_prefix(current_dir).unwrap_or(&path).display(),
+        when.format(format),
+        relative_day(days)
+    );
+    Ok(())
+}
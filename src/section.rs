@@ -0,0 +1,135 @@
+use crate::error::Error;
+use crate::front_matter::{Extension, Format};
+use crate::project::Project;
+use clap::Args;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Fields of new section indexes, the `[index]` table of the config
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexConfig {
+    /// How the section's pages are sorted, e.g. `date` or `weight`
+    pub sort_by: Option<String>,
+    /// Number of pages per page of the section's listing
+    pub paginate_by: Option<u32>,
+    /// Template the section is rendered with
+    pub template: Option<String>,
+    /// Template the section's pages are rendered with
+    pub page_template: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct SectionArguments {
+    /// Name of the section's directory, may be nested like `rust/async`
+    name: String,
+
+    /// Title of the section, instead of deriving it from the name
+    #[arg(long)]
+    title: Option<String>,
+}
+
+/// Runs the `section` subcommand, creating the section's directory and index
+pub fn run(args: SectionArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = project.section_config(Some(&args.name))?;
+    let content_dir = project.content_dir_or_create(&config, project.preset, false)?;
+    let dir = content_dir.join(&args.name);
+    fs::create_dir_all(&dir)
+        .map_err(|e| Error::from_error("Failed to create section directory", &e))?;
+
+    let mut front_matter = index_front_matter(&dir, &config.index);
+    if let Some(title) = args.title {
+        front_matter.insert("title".to_string(), Value::String(title));
+    }
+    let format = config
+        .format
+        .or_else(|| project.preset.map(|p| p.default_format()))
+        .unwrap_or_default();
+    let extension = config.ext.unwrap_or_default();
+
+    let path = write_index(&dir, &front_matter, format, extension)?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+/// Title of a section named after its directory, e.g. `Rust Async` for
+/// `rust-async`
+pub fn title(dir: &Path) -> String {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    name.split(['-', '_', ' '])
+        .filter(|w| !w.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The directories of a nested section, like `rust/async`, that don't exist yet
+/// in `parent`
+pub fn missing_dirs(parent: &Path, section: &str) -> Vec<PathBuf> {
+    Path::new(section)
+        .iter()
+        .scan(parent.to_path_buf(), |dir, name| {
+            dir.push(name);
+            Some(dir.clone())
+        })
+        .filter(|d| !d.exists())
+        .collect()
+}
+
+/// Writes the `_index` file of a section directory, unless it has one
+pub fn write_index(
+    dir: &Path,
+    front_matter: &Table,
+    format: Format,
+    extension: Extension,
+) -> Result<PathBuf, Error> {
+    let path = dir.join(format!("_index.{}", extension.as_str()));
+    if path.exists() {
+        return Err(Error::from_string(
+            format!("'{}' already exists", path.display()).as_str(),
+        ));
+    }
+
+    fs::write(&path, extension.render(front_matter, format)?)
+        .map_err(|e| Error::from_error(&format!("Failed to write '{}'", path.display()), &e))?;
+    Ok(path)
+}
+
+/// The front matter of a new section's index, its title and the configured
+/// fields
+pub fn index_front_matter(dir: &Path, config: &IndexConfig) -> Table {
+    let mut front_matter = Table::new();
+    front_matter.insert("title".to_string(), Value::String(title(dir)));
+    let fields = [
+        ("sort_by", config.sort_by.clone().map(Value::String)),
+        (
+            "paginate_by",
+            config.paginate_by.map(|p| Value::Integer(p.into())),
+        ),
+        ("template", config.template.clone().map(Value::String)),
+        (
+            "page_template",
+            config.page_template.clone().map(Value::String),
+        ),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            front_matter.insert(key.to_string(), value);
+        }
+    }
+
+    front_matter
+}
@@ -0,0 +1,158 @@
+use deunicode::deunicode;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Words left out of slugs with `stop_words = true`
+pub const STOP_WORDS: [&str; 24] = [
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "if", "in", "into",
+    "is", "it", "of", "on", "or", "so", "the", "to", "was", "with",
+];
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SlugStyle {
+    /// Only letters and digits, with single hyphens between words
+    #[default]
+    Full,
+    /// Spaces become hyphens and quotes and parentheses are dropped, anything
+    /// else is kept as is
+    Basic,
+}
+
+/// How titles are turned into slugs, the `[slug]` table of the config
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SlugConfig {
+    pub style: SlugStyle,
+    /// Keep non-ASCII characters instead of transliterating them
+    pub unicode: bool,
+    /// Leave out common words like "a", "the" and "and"
+    pub stop_words: bool,
+    /// Longest slug allowed, longer slugs are cut off after the last whole word
+    pub max_length: Option<usize>,
+    /// Text in titles to replace before slugifying, e.g. `"c#" = "csharp"`
+    pub replacements: BTreeMap<String, String>,
+    /// Write the slug to the front matter, so the URL survives renaming the file
+    pub field: bool,
+}
+
+/// Turns a title into a slug, `lang` is the language of the title, e.g. `de`
+/// or `nb-NO`, which picks the transliteration rules for it
+pub fn create_safe_file_name(title: &str, lang: Option<&str>, config: &SlugConfig) -> String {
+    let mut title = title.to_string();
+    for (from, to) in lang.map_or(&[][..], |l| locale_rules(l, config.unicode)) {
+        title = title.replace(from, to);
+    }
+
+    let title = replace(&title.to_lowercase(), &config.replacements);
+    let title = if config.unicode {
+        title
+    } else {
+        deunicode(&title)
+    };
+
+    let title = title.replace(['\'', '"', '(', ')'], "").to_lowercase();
+    let mut words = match config.style {
+        SlugStyle::Full => title
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>(),
+        SlugStyle::Basic => title.split(' ').collect::<Vec<_>>(),
+    };
+
+    if config.stop_words {
+        let remaining = words
+            .iter()
+            .copied()
+            .filter(|w| !STOP_WORDS.contains(w))
+            .collect::<Vec<_>>();
+        // A title of only stop words keeps them all
+        if remaining.iter().any(|w| !w.is_empty()) {
+            words = remaining;
+        }
+    }
+
+    let slug = words.join("-");
+    match config.max_length {
+        Some(max_length) => truncate(&slug, max_length),
+        None => slug,
+    }
+}
+
+// Spellings the generic transliteration gets wrong for the language
+fn locale_rules(lang: &str, unicode: bool) -> &'static [(&'static str, &'static str)] {
+    let language = lang
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match (language.as_str(), unicode) {
+        // Dotted and dotless i are separate letters, which lowercasing doesn't know
+        ("tr" | "az", _) => &[("İ", "i"), ("I", "ı")],
+        (_, true) => &[],
+        ("de", _) => &[
+            ("ä", "ae"),
+            ("ö", "oe"),
+            ("ü", "ue"),
+            ("Ä", "Ae"),
+            ("Ö", "Oe"),
+            ("Ü", "Ue"),
+            ("ß", "ss"),
+        ],
+        ("nb" | "nn" | "no" | "da", _) => &[
+            ("æ", "ae"),
+            ("ø", "oe"),
+            ("å", "aa"),
+            ("Æ", "Ae"),
+            ("Ø", "Oe"),
+            ("Å", "Aa"),
+        ],
+        _ => &[],
+    }
+}
+
+// Longer patterns go first, so `c++` is replaced before `c`
+fn replace(title: &str, replacements: &BTreeMap<String, String>) -> String {
+    let mut patterns = replacements
+        .iter()
+        .map(|(from, to)| (from.to_lowercase(), to))
+        .filter(|(from, _)| !from.is_empty())
+        .collect::<Vec<_>>();
+    patterns.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+
+    let mut replaced = String::new();
+    let mut rest = title;
+    'outer: while let Some(c) = rest.chars().next() {
+        for (from, to) in &patterns {
+            if let Some(after) = rest.strip_prefix(from.as_str()) {
+                replaced.push_str(to);
+                rest = after;
+                continue 'outer;
+            }
+        }
+        replaced.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    replaced
+}
+
+// Cuts at the last `-` that fits, or mid-word if the first word is too long
+fn truncate(slug: &str, max_length: usize) -> String {
+    if slug.chars().count() <= max_length {
+        return slug.to_string();
+    }
+
+    let cut = slug
+        .char_indices()
+        .nth(max_length)
+        .map_or(slug.len(), |(i, _)| i);
+    if slug[cut..].starts_with('-') {
+        return slug[..cut].to_string();
+    }
+    match slug[..cut].rfind('-') {
+        Some(end) if end > 0 => slug[..end].to_string(),
+        _ => slug[..cut].to_string(),
+    }
+}
@@ -0,0 +1,69 @@
+use crate::error::Error;
+use crate::front_matter;
+use crate::project::Project;
+use crate::template;
+use clap::Args;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct SnippetArguments {
+    /// Name of the snippet in the config's `[snippets]`
+    name: String,
+
+    /// Post to add the snippet to
+    post: PathBuf,
+
+    /// Values for the snippet's `{{ placeholders }}`, e.g. `id=dQw4w9WgXcQ`
+    #[arg(value_name = "KEY=VALUE", value_parser = front_matter::parse_field)]
+    values: Vec<(String, toml::Value)>,
+}
+
+/// Runs the `snippet` subcommand, adding a snippet from the config to the end
+/// of a post
+pub fn run(args: SnippetArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let config = &project.config;
+    let snippet = config.snippets.get(&args.name).ok_or_else(|| {
+        Error::from_string(
+            format!(
+                "There's no snippet named '{}'{}",
+                args.name,
+                if config.snippets.is_empty() {
+                    ", add them to [snippets] in the config".to_string()
+                } else {
+                    format!(
+                        ", only {}",
+                        config
+                            .snippets
+                            .keys()
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
+            )
+            .as_str(),
+        )
+    })?;
+
+    let variables = config
+        .variables
+        .iter()
+        .chain(args.values.iter().map(|(k, v)| (k, v)))
+        .map(|(k, v)| (k.clone(), template::value_text(v)))
+        .collect::<BTreeMap<_, _>>();
+    let snippet = template::render(snippet.trim(), &variables);
+
+    let path = current_dir.join(&args.post);
+    let mut contents = fs::read_to_string(&path)
+        .map_err(|e| Error::from_error(&format!("Failed to read '{}'", path.display()), &e))?;
+    contents.truncate(contents.trim_end().len());
+    contents.push_str("\n\n");
+    contents.push_str(&snippet);
+    contents.push('\n');
+
+    fs::write(&path, contents)
+        .map_err(|e| Error::from_error(&format!("Failed to write '{}'", path.display()), &e))
+}
@@ -0,0 +1,394 @@
+use crate::document::Document;
+use crate::error::Error;
+use crate::post;
+use crate::project::Project;
+use crate::prompt;
+use clap::{Args, Subcommand};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+#[derive(Args, Debug)]
+pub struct TagsArguments {
+    #[command(subcommand)]
+    action: Option<TagsAction>,
+
+    /// Sort the tags by name instead of by how many posts have them
+    #[arg(long)]
+    by_name: bool,
+
+    /// Print only the names of the tags, for scripts
+    #[arg(long)]
+    names_only: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagsAction {
+    /// Rename a tag in every post that has it
+    Rename {
+        old: String,
+        new: String,
+
+        /// Show which posts would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Replace several tags with one in every post that has any of them
+    Merge {
+        #[arg(required = true)]
+        tags: Vec<String>,
+
+        /// Tag the posts get instead
+        #[arg(long)]
+        into: String,
+
+        /// Show which posts would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List the tags that only differ in case or hyphens, like `Rust` and
+    /// `rust` or `web-dev` and `webdev`
+    Duplicates {
+        /// Replace each of them with the most used one
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+/// Runs the `tags` subcommand, printing every tag on the site and how many
+/// posts have it, or changing the tags of the posts
+pub fn run(args: TagsArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    match args.action {
+        Some(TagsAction::Rename { old, new, dry_run }) => {
+            return retag(&project, current_dir, dry_run, |tags| {
+                tags.iter()
+                    .map(|t| if *t == old { new.clone() } else { t.clone() })
+                    .collect()
+            })
+        }
+        Some(TagsAction::Merge {
+            tags: merged,
+            into,
+            dry_run,
+        }) => {
+            return retag(&project, current_dir, dry_run, |tags| {
+                tags.iter()
+                    .map(|t| {
+                        if merged.contains(t) {
+                            into.clone()
+                        } else {
+                            t.clone()
+                        }
+                    })
+                    .collect()
+            })
+        }
+        Some(TagsAction::Duplicates { fix }) => {
+            let site_tags = site_tags(&project)?;
+            let groups = duplicates(&site_tags);
+            for group in &groups {
+                let counts = group
+                    .iter()
+                    .map(|t| format!("{} ({})", t, site_tags[t]))
+                    .collect::<Vec<_>>();
+                println!("{}", counts.join(", "));
+            }
+            if !fix {
+                return Ok(());
+            }
+            return retag(&project, current_dir, false, |tags| {
+                tags.iter()
+                    .map(|t| {
+                        groups
+                            .iter()
+                            .find(|g| g.contains(t))
+                            .map_or(t.clone(), |g| g[0].clone())
+                    })
+                    .collect()
+            });
+        }
+        None => {}
+    }
+
+    let mut tags = site_tags(&project)?.into_iter().collect::<Vec<_>>();
+    if !args.by_name {
+        tags.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    }
+
+    let width = tags
+        .iter()
+        .map(|(_, c)| c.to_string().len())
+        .max()
+        .unwrap_or(0);
+    for (tag, count) in tags {
+        if args.names_only {
+            println!("{}", tag);
+            continue;
+        }
+        println!("{:>width$}  {}", count, tag, width = width);
+    }
+    Ok(())
+}
+
+/// How tags given for new posts are tidied up, the `[tag_rules]` table of the
+/// config
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagRules {
+    pub lowercase: bool,
+    /// Join the words of a tag with hyphens, `web dev` becomes `web-dev`
+    pub hyphens: bool,
+    /// Leave out `#` at the start, `#rust` becomes `rust`
+    pub strip_hash: bool,
+    /// The only tags new posts may have, unless `--allow-new-tags` is given
+    pub allowed: Option<Vec<String>>,
+    /// File in the project with a tag allowed on each line, as well as the
+    /// `allowed` ones
+    pub allowed_file: Option<PathBuf>,
+    /// Whether a tag that isn't allowed stops the post from being created
+    pub unknown: UnknownTags,
+}
+
+/// What to do about tags that aren't allowed
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownTags {
+    #[default]
+    Fail,
+    Warn,
+}
+
+impl TagRules {
+    pub fn normalize(&self, tag: &str) -> String {
+        let mut tag = tag.trim();
+        if self.strip_hash {
+            tag = tag.trim_start_matches('#').trim_start();
+        }
+        let tag = if self.lowercase {
+            tag.to_lowercase()
+        } else {
+            tag.to_string()
+        };
+        if self.hyphens {
+            tag.split_whitespace().collect::<Vec<_>>().join("-")
+        } else {
+            tag
+        }
+    }
+
+    /// Complains about the tags that aren't allowed, if only some are. The
+    /// allowed file is relative to `root`.
+    pub fn check(&self, tags: &[String], root: &Path) -> Result<(), Error> {
+        if self.allowed.is_none() && self.allowed_file.is_none() {
+            return Ok(());
+        }
+
+        let mut allowed = self.allowed.clone().unwrap_or_default();
+        if let Some(file) = &self.allowed_file {
+            let path = root.join(file);
+            let contents = fs::read_to_string(&path).map_err(|e| {
+                Error::from_error(&format!("Failed to read '{}'", path.display()), &e)
+            })?;
+            allowed.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(String::from),
+            );
+        }
+
+        let unknown = tags
+            .iter()
+            .filter(|t| !allowed.contains(t))
+            .map(|t| format!("'{}'", t))
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        let message = format!(
+            "Tags that aren't allowed: {}, --allow-new-tags creates the post anyway",
+            unknown.join(", ")
+        );
+        match self.unknown {
+            UnknownTags::Fail => Err(Error::from_string(&message)),
+            UnknownTags::Warn => {
+                eprintln!("{}", message);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// How many of the most used tags the picker shows
+pub const PICKED_TAGS: usize = 30;
+
+/// Lets the user pick tags for a new post from the ones on the site, or write
+/// new ones. The site's tags in the title are suggested instead, if there are
+/// any. Nothing is asked without a user, with `pick_tags = false` or on a site
+/// without tags.
+pub fn pick(
+    current_dir: &Path,
+    profile: Option<String>,
+    title: &str,
+) -> Result<Vec<String>, Error> {
+    if !prompt::is_interactive() {
+        return Ok(Vec::new());
+    }
+    let project = Project::load(current_dir, profile)?;
+    if !project.config.pick_tags.unwrap_or(true) {
+        return Ok(Vec::new());
+    }
+    // A site that can't be found yet has no tags
+    let Ok(tags) = site_tags(&project) else {
+        return Ok(Vec::new());
+    };
+    if tags.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let suggested = suggest(title, tags.keys());
+    if !suggested.is_empty() {
+        let answer = prompt::suggest(
+            "Tags, separated by spaces or commas (- for none):",
+            &suggested.join(" "),
+        )?;
+        return Ok(prompt::words(&answer)
+            .into_iter()
+            .filter(|w| *w != "-")
+            .map(String::from)
+            .collect());
+    }
+
+    let mut tags = tags.into_iter().collect::<Vec<_>>();
+    tags.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    let tags = tags
+        .into_iter()
+        .take(PICKED_TAGS)
+        .map(|(tag, _)| tag)
+        .collect::<Vec<_>>();
+    prompt::pick(
+        "Tags, by number or new ones, separated by spaces or commas (none if empty):",
+        &tags,
+    )
+}
+
+/// The tags whose words are in the title, in the order they're in it, e.g.
+/// `rust` and `async` for "Async Rust in practice"
+pub fn suggest<'a>(title: &str, tags: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let title_words = words_of(title);
+    let mut suggested = tags
+        .filter_map(|tag| {
+            let tag_words = words_of(tag);
+            if tag_words.is_empty() {
+                return None;
+            }
+            let position = title_words
+                .windows(tag_words.len())
+                .position(|w| w == tag_words.as_slice())?;
+            Some((position, tag.clone()))
+        })
+        .collect::<Vec<_>>();
+    suggested.sort();
+    suggested.into_iter().map(|(_, tag)| tag).collect()
+}
+
+/// The tags that are the same but for case, hyphens, underscores and spaces,
+/// in groups with the one to keep, the most used, first
+fn duplicates(tags: &BTreeMap<String, usize>) -> Vec<Vec<String>> {
+    let mut groups = BTreeMap::<String, Vec<String>>::new();
+    for tag in tags.keys() {
+        let key = tag.to_lowercase().replace(['-', '_', ' '], "");
+        groups.entry(key).or_default().push(tag.clone());
+    }
+
+    groups
+        .into_values()
+        .filter(|g| g.len() > 1)
+        .map(|mut group| {
+            // Lowercase ones first when they're as used as the others
+            group.sort_by_key(|t| {
+                (
+                    std::cmp::Reverse(tags[t]),
+                    *t != t.to_lowercase(),
+                    t.clone(),
+                )
+            });
+            group
+        })
+        .collect()
+}
+
+fn words_of(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// The tags of the posts in the content and drafts directories, and how many
+/// posts have each of them
+pub fn site_tags(project: &Project) -> Result<BTreeMap<String, usize>, Error> {
+    let config = &project.config;
+    let mut dirs = vec![project.content_dir(config, project.preset)?];
+    dirs.extend(project.drafts_dir(config, project.preset));
+
+    let mut tags = BTreeMap::new();
+    for post in post::load_posts(&dirs) {
+        for tag in post::field_strings(&post.front_matter, "tags") {
+            *tags.entry(tag).or_insert(0) += 1;
+        }
+    }
+    Ok(tags)
+}
+
+// Changes the tags of every post on the site with `change`, printing the
+// posts whose tags it changed. A tag that ends up in a post twice is only
+// kept the first time.
+fn retag(
+    project: &Project,
+    current_dir: &Path,
+    dry_run: bool,
+    change: impl Fn(&[String]) -> Vec<String>,
+) -> Result<(), Error> {
+    let config = &project.config;
+    let mut dirs = vec![project.content_dir(config, project.preset)?];
+    dirs.extend(project.drafts_dir(config, project.preset));
+
+    for post in post::load_posts(&dirs) {
+        let tags = post::field_strings(&post.front_matter, "tags");
+        let mut changed = Vec::new();
+        for tag in change(&tags) {
+            if !changed.contains(&tag) {
+                changed.push(tag);
+            }
+        }
+        if changed == tags {
+            continue;
+        }
+
+        let relative = post.path.strip_prefix(current_dir).unwrap_or(&post.path);
+        if dry_run {
+            println!(
+                "{}  {} -> {}",
+                relative.display(),
+                tags.join(", "),
+                changed.join(", ")
+            );
+            continue;
+        }
+        let mut document = Document::read(&post.path)?;
+        document.set(
+            "tags",
+            Value::Array(changed.into_iter().map(Value::String).collect()),
+            "",
+        );
+        document.write()?;
+        println!("{}", relative.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,381 @@
+use crate::document::between;
+use crate::error::Error;
+use crate::front_matter::FrontMatter;
+use crate::hugo::{self, ARCHETYPES_DIRECTORY};
+use crate::init::TEMPLATES_DIRECTORY;
+use crate::post::{parse_front_matter, POST_EXTENSIONS};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use toml::{Table, Value};
+
+/// Directories templates are looked for in, relative to the project root
+pub const TEMPLATE_DIRECTORIES: [&str; 3] =
+    [TEMPLATES_DIRECTORY, "templates", ARCHETYPES_DIRECTORY];
+
+/// Fields of a template's front matter that are always the new post's own
+const POST_FIELDS: [&str; 7] = [
+    "title", "date", "slug", "updated", "lastmod", "pubDate", "id",
+];
+
+/// A skeleton for new posts, with front matter fields to add to them and a
+/// body to start them with
+pub struct Template {
+    pub front_matter: Table,
+    pub body: String,
+}
+
+impl Template {
+    /// Finds the template named `name`, e.g. `review` for `templates/review.md`
+    pub fn find(root: &Path, name: &str) -> Result<Template, Error> {
+        match path(root, name) {
+            Some(path) => Template::read(&path),
+            None => {
+                let names = names(root);
+                Err(Error::from_string(
+                    format!(
+                        "There's no template named '{}' in {}{}",
+                        name,
+                        TEMPLATE_DIRECTORIES.join(" or "),
+                        if names.is_empty() {
+                            String::new()
+                        } else {
+                            format!(", only {}", names.join(", "))
+                        }
+                    )
+                    .as_str(),
+                ))
+            }
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Template, Error> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Error::from_error(&format!("Failed to read '{}'", path.display()), &e))?;
+
+        Template::parse(&contents).ok_or(Error::from_string(
+            format!("Failed to parse the front matter of '{}'", path.display()).as_str(),
+        ))
+    }
+
+    /// Reads a template from its text, `None` if the front matter is broken.
+    /// Placeholders may be left unquoted, like `date = {{ .Date }}` in Hugo's
+    /// archetypes.
+    pub fn parse(contents: &str) -> Option<Template> {
+        // Templates without front matter are only a body
+        let body = between(contents, "+++").or_else(|| between(contents, "---"));
+        match body {
+            Some((_, body)) => {
+                let (masked, placeholders) = mask_placeholders(contents);
+                let mut front_matter = parse_front_matter(&masked, false)?;
+                unmask_fields(&mut front_matter, &placeholders);
+                Some(Template {
+                    front_matter,
+                    body: body.to_string(),
+                })
+            }
+            None => Some(Template {
+                front_matter: Table::new(),
+                body: contents.to_string(),
+            }),
+        }
+    }
+
+    /// Fills in the `{{ name }}` placeholders in the body and the front matter
+    pub fn render(&mut self, variables: &BTreeMap<String, String>) {
+        self.body = render(&self.body, variables);
+        render_fields(&mut self.front_matter, variables);
+    }
+
+    /// Adds the template's fields to the front matter of a new post, leaving
+    /// the ones already set as they are
+    pub fn apply(&self, front_matter: &mut FrontMatter) {
+        apply_fields(&self.front_matter, front_matter);
+    }
+}
+
+/// Replaces `{{ name }}` in `text` with the variable's value, Hugo's names like
+/// `{{ .Title }}` and environment variables like `{{ env.USER }}` work too.
+/// Placeholders for variables that don't exist are left as they are, so
+/// shortcodes like `{{< figure >}}` and other template syntax pass through.
+pub fn render(text: &str, variables: &BTreeMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after
+            .find("}}")
+            .and_then(|end| Some((lookup(after[..end].trim(), variables)?, end)));
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(&value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                rendered.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+// Environment variables are looked up as `env.NAME`
+fn lookup(name: &str, variables: &BTreeMap<String, String>) -> Option<String> {
+    if let Some(name) = name.strip_prefix("env.") {
+        return env::var(name).ok();
+    }
+
+    variables
+        .get(name)
+        .or_else(|| variables.get(hugo::variable(name)?))
+        .cloned()
+}
+
+/// The text a config value is put into templates as
+pub fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(values) => values.iter().map(value_text).collect::<Vec<_>>().join(", "),
+        _ => value.to_string(),
+    }
+}
+
+/// Whether there's a template named `name`
+pub fn exists(root: &Path, name: &str) -> bool {
+    path(root, name).is_some()
+}
+
+fn path(root: &Path, name: &str) -> Option<PathBuf> {
+    TEMPLATE_DIRECTORIES
+        .iter()
+        .flat_map(|dir| POST_EXTENSIONS.map(|ext| root.join(dir).join(format!("{}.{}", name, ext))))
+        .find(|p| p.is_file())
+}
+
+// The names of the templates there are, for error messages
+fn names(root: &Path) -> Vec<String> {
+    let mut names = TEMPLATE_DIRECTORIES
+        .iter()
+        .filter_map(|dir| fs::read_dir(root.join(dir)).ok())
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|e| POST_EXTENSIONS.iter().any(|x| e == *x))
+        })
+        .filter_map(|p| Some(p.file_stem()?.to_string_lossy().to_string()))
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+}
+
+// Zola's `[extra]` and `[taxonomies]` tables in templates are read like the
+// fields in them were at the top
+fn apply_fields(fields: &Table, front_matter: &mut FrontMatter) {
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            (key, _) if POST_FIELDS.contains(&key) => {}
+            ("extra" | "taxonomies", Value::Table(table)) => apply_fields(table, front_matter),
+            ("layout", Value::String(layout)) => {
+                front_matter.layout.get_or_insert_with(|| layout.clone());
+            }
+            ("description", Value::String(description)) => {
+                front_matter
+                    .description
+                    .get_or_insert_with(|| description.clone());
+            }
+            ("permalink", Value::String(permalink)) => {
+                front_matter
+                    .permalink
+                    .get_or_insert_with(|| permalink.clone());
+            }
+            ("draft", Value::Boolean(true)) => front_matter.draft = Some(true),
+            ("weight", Value::Integer(weight)) => {
+                front_matter.weight.get_or_insert(*weight);
+            }
+            ("authors", Value::Array(authors)) if front_matter.authors.is_empty() => {
+                add_terms(&mut front_matter.authors, authors)
+            }
+            ("aliases", Value::Array(aliases)) => add_terms(&mut front_matter.aliases, aliases),
+            ("tags", Value::Array(terms)) => add_terms(&mut front_matter.tags, terms),
+            ("categories", Value::Array(terms)) => add_terms(&mut front_matter.categories, terms),
+            // The post's own fields aren't repeated in `extra`, whatever's in
+            // the template
+            (
+                "layout" | "description" | "permalink" | "draft" | "weight" | "authors" | "aliases"
+                | "tags" | "categories",
+                _,
+            ) => {}
+            (key, value) => {
+                front_matter
+                    .extra
+                    .entry(key)
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+}
+
+// Swaps the placeholders for tokens that can be parsed, in quotes unless
+// they're in a quoted string already, and returns the placeholders the tokens
+// stand for
+fn mask_placeholders(contents: &str) -> (String, Vec<String>) {
+    let mut placeholders = Vec::new();
+    let mut masked = String::with_capacity(contents.len());
+    for line in contents.split_inclusive('\n') {
+        let mut masked_line = String::new();
+        let mut rest = line;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}").map(|e| start + e + 2) else {
+                break;
+            };
+            masked_line.push_str(&rest[..start]);
+            let token = format!("@@newpost-placeholder-{}@@", placeholders.len());
+            if is_in_quotes(&masked_line) {
+                masked_line.push_str(&token);
+            } else {
+                masked_line.push_str(&format!("\"{}\"", token));
+            }
+            placeholders.push(rest[start..end].to_string());
+            rest = &rest[end..];
+        }
+        masked_line.push_str(rest);
+        masked.push_str(&masked_line);
+    }
+    (masked, placeholders)
+}
+
+// Whether the end of the line is inside a quoted string
+fn is_in_quotes(line: &str) -> bool {
+    let mut quote = None;
+    let mut escaped = false;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            _ => {}
+        }
+        escaped = false;
+    }
+    quote.is_some()
+}
+
+fn unmask_fields(fields: &mut Table, placeholders: &[String]) {
+    let unmask = |text: &mut String| {
+        for (i, placeholder) in placeholders.iter().enumerate().rev() {
+            *text = text.replace(&format!("@@newpost-placeholder-{}@@", i), placeholder);
+        }
+    };
+    for (_, value) in fields.iter_mut() {
+        match value {
+            Value::String(text) => unmask(text),
+            Value::Table(table) => unmask_fields(table, placeholders),
+            Value::Array(values) => {
+                for value in values.iter_mut() {
+                    if let Value::String(text) = value {
+                        unmask(text);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render_fields(fields: &mut Table, variables: &BTreeMap<String, String>) {
+    for (_, value) in fields.iter_mut() {
+        match value {
+            Value::String(text) => *text = render(text, variables),
+            Value::Table(table) => render_fields(table, variables),
+            Value::Array(values) => {
+                for value in values.iter_mut() {
+                    if let Value::String(text) = value {
+                        *text = render(text, variables);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn add_terms(terms: &mut Vec<String>, new_terms: &[Value]) {
+    for term in new_terms.iter().filter_map(Value::as_str) {
+        if !terms.iter().any(|t| t == term) {
+            terms.push(term.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::front_matter::{to_toml_date, Format};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn reads_hugo_default_archetype() {
+        let archetype = "+++\n\
+            title = '{{ replace .File.ContentBaseName \"-\" \" \" | title }}'\n\
+            date = {{ .Date }}\n\
+            draft = true\n\
+            +++\n";
+        let mut template = Template::parse(archetype).expect("the archetype parses");
+        let variables = BTreeMap::from([
+            ("title".to_string(), "My first post".to_string()),
+            ("date".to_string(), "2024-06-01".to_string()),
+        ]);
+        template.render(&variables);
+
+        assert_eq!(
+            template.front_matter.get("title"),
+            Some(&Value::String("My first post".to_string()))
+        );
+        assert_eq!(
+            template.front_matter.get("date"),
+            Some(&Value::String("2024-06-01".to_string()))
+        );
+        assert_eq!(
+            template.front_matter.get("draft"),
+            Some(&Value::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn applies_post_fields_without_repeating_them() {
+        let template = Template::parse(
+            "+++\n\
+            title = \"Old\"\n\
+            weight = 3\n\
+            draft = false\n\
+            authors = [\"Someone\"]\n\
+            aliases = [\"/old/\"]\n\
+            [extra]\n\
+            authors = [\"Tester\"]\n\
+            toc = true\n\
+            +++\n",
+        )
+        .expect("the template parses");
+        let date = to_toml_date(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+        let mut front_matter = FrontMatter::new("New", date, Vec::new());
+        front_matter.authors = vec!["Author".to_string()];
+        template.apply(&mut front_matter);
+
+        assert_eq!(front_matter.weight, Some(3));
+        assert_eq!(front_matter.authors, ["Author"]);
+        assert_eq!(front_matter.aliases, ["/old/"]);
+        let table = front_matter.to_table(Format::Toml, None).unwrap();
+        let extra = table["extra"].as_table().unwrap();
+        assert_eq!(extra.keys().collect::<Vec<_>>(), ["toc"]);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::date::Zone;
+use crate::document::Document;
+use crate::error::Error;
+use crate::front_matter::{format_date, to_toml_date, to_toml_datetime};
+use crate::project::Project;
+use chrono::{DateTime, FixedOffset};
+use clap::Args;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Fields that hold when a post was last updated, by site generator
+pub const UPDATED_FIELDS: [&str; 4] = ["updated", "lastmod", "updatedDate", "modified"];
+
+#[derive(Args, Debug)]
+pub struct TouchArguments {
+    /// Post to mark as updated
+    post: PathBuf,
+}
+
+/// Runs the `touch` subcommand, setting the post's updated field to now
+pub fn run(args: TouchArguments, current_dir: &Path) -> Result<(), Error> {
+    let project = Project::load(current_dir, None)?;
+    let mut document = Document::read(&current_dir.join(&args.post))?;
+    let table = document.table();
+
+    let date_field = ["date", "pubDate"]
+        .into_iter()
+        .find(|k| table.contains_key(*k))
+        .unwrap_or("date");
+    let updated_field = UPDATED_FIELDS
+        .into_iter()
+        .find(|k| table.contains_key(*k))
+        .or_else(|| project.preset.map(|p| p.updated_field()))
+        .unwrap_or("updated");
+
+    let zone = Zone::from_setting(project.config.timezone.as_deref())?;
+    let updated = date_value(
+        table.get(date_field),
+        zone.now(),
+        project.config.date_format.as_deref(),
+    )?;
+
+    document.set(updated_field, updated, date_field);
+    document.write()
+}
+
+/// `now` written the same way as `existing`, which may or may not have a time
+pub fn date_value(
+    existing: Option<&Value>,
+    now: DateTime<FixedOffset>,
+    date_format: Option<&str>,
+) -> Result<Value, Error> {
+    let value = match existing {
+        _ if date_format.is_some() => Value::String(format_date(
+            &to_toml_datetime(now),
+            date_format.unwrap_or_default(),
+        )?),
+        Some(Value::Datetime(date)) if date.time.is_none() => {
+            Value::Datetime(to_toml_date(now.date_naive()))
+        }
+        Some(Value::String(date)) if date.len() <= 10 => {
+            Value::Datetime(to_toml_date(now.date_naive()))
+        }
+        // Hexo's `YYYY-MM-DD HH:mm:ss`
+        Some(Value::String(date)) if date.as_bytes().get(10) == Some(&b' ') => {
+            Value::String(now.format("%Y-%m-%d %H:%M:%S").to_string())
+        }
+        _ => Value::Datetime(to_toml_datetime(now)),
+    };
+    Ok(value)
+}
@@ -0,0 +1,120 @@
+use crate::collision::BUNDLE_INDEX;
+use crate::config::Config;
+use crate::date;
+use crate::document::Document;
+use crate::hugo::{self, SiteConfig};
+use crate::post;
+use crate::preset::Preset;
+use std::path::{Component, Path};
+use toml::{Table, Value};
+
+/// Where a post in `content_dir` ends up on the site, with the site's base URL
+/// in front if it's known
+pub fn post_url(
+    config: &Config,
+    root: &Path,
+    preset: Option<Preset>,
+    content_dir: &Path,
+    path: &Path,
+    front_matter: &Table,
+) -> String {
+    let base_url = config
+        .base_url
+        .clone()
+        .or_else(|| site_base_url(root, preset))
+        .unwrap_or_default();
+
+    format!(
+        "{}{}",
+        base_url.trim_end_matches('/'),
+        post_path(preset, content_dir, path, front_matter)
+    )
+}
+
+/// The path of a post's URL on the site, like `/blog/my-post/`. Follows the
+/// generators' default permalinks, or the post's own `permalink`.
+pub fn post_path(
+    preset: Option<Preset>,
+    content_dir: &Path,
+    path: &Path,
+    front_matter: &Table,
+) -> String {
+    if let Some(permalink) = post::field(front_matter, "permalink").and_then(Value::as_str) {
+        return format!("/{}", permalink.trim_start_matches('/'));
+    }
+
+    let relative = path.strip_prefix(content_dir).unwrap_or(path);
+    let mut parts = relative
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // A bundle's URL is its directory's
+    if stem != BUNDLE_INDEX {
+        let slug = post::field(front_matter, "slug")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or(stem);
+        parts.push(slug);
+    }
+
+    // Jekyll's and Hexo's posts go under their date, `2024-06-01-title` becomes
+    // `2024/06/01/title`
+    if let Some(Preset::Jekyll | Preset::Hexo) = preset {
+        let last = parts.pop().unwrap_or_default();
+        match date::split_dated_name(&last) {
+            Some((date, title)) => {
+                parts = vec![date.format("%Y/%m/%d").to_string(), title.to_string()]
+            }
+            None => parts.push(last),
+        }
+        if preset == Some(Preset::Jekyll) {
+            return format!("/{}.html", parts.join("/"));
+        }
+    }
+
+    format!("/{}/", parts.join("/"))
+}
+
+/// Adds an old URL of a post to its aliases, so links to it keep working
+pub fn add_alias(document: &mut Document, preset: Option<Preset>, alias: &str) {
+    let field = preset.map_or("aliases", |p| p.aliases_field());
+    let mut aliases = post::field_strings(&document.table(), field);
+    if aliases.iter().any(|a| a == alias) {
+        return;
+    }
+
+    aliases.push(alias.to_string());
+    let aliases = aliases.into_iter().map(Value::String).collect();
+    document.set(field, Value::Array(aliases), "");
+}
+
+// The base URL in the site generator's own config
+fn site_base_url(root: &Path, preset: Option<Preset>) -> Option<String> {
+    let from_file = |file: &str, key: &str| {
+        hugo::parse(&root.join(file))?
+            .get(key)?
+            .as_str()
+            .map(String::from)
+    };
+
+    match preset? {
+        Preset::Hugo => SiteConfig::read(root).base_url,
+        Preset::Zola => from_file("config.toml", "base_url"),
+        Preset::Jekyll => Some(
+            from_file("_config.yml", "url").unwrap_or_default()
+                + &from_file("_config.yml", "baseurl").unwrap_or_default(),
+        )
+        .filter(|u| !u.is_empty()),
+        Preset::Hexo => from_file("_config.yml", "url"),
+        Preset::Eleventy | Preset::Astro => None,
+    }
+}